@@ -0,0 +1,71 @@
+use std::{env, path::Path, process};
+
+use nes_emulator::{console::Console, mem::rom::RomError, render::frame::Frame};
+
+const DEFAULT_FRAMES: u32 = 60;
+
+// Runs `frames` frames of the ROM at `path` headless and hashes the last one
+fn run_rom(path: &Path, frames: u32) -> Result<u64, RomError> {
+    let mut console = Console::from_file(path)?;
+    let mut frame = Frame::new();
+    console.run_frames(frames, &mut frame);
+    Ok(console.frame_hash())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let Some(file_path) = args.get(1) else {
+        eprintln!("Usage: run_rom <rom.nes> [frames]");
+        process::exit(2);
+    };
+    let frames = match args.get(2).map(|arg| arg.parse()) {
+        Some(Ok(frames)) => frames,
+        Some(Err(_)) => {
+            eprintln!("Frame count must be a number");
+            process::exit(2);
+        }
+        None => DEFAULT_FRAMES,
+    };
+
+    match run_rom(Path::new(file_path), frames) {
+        Ok(hash) => println!("{:016x}", hash),
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod run_rom_tests {
+    use super::*;
+    use nes_emulator::mem::rom::Rom;
+
+    #[test]
+    fn test_run_rom_hash_is_stable() {
+        let mut raw = Rom::create_rom_data(1, 1, 0x00, 0x00, false);
+        #[rustfmt::skip]
+        let program = [
+            0x2C, 0x02, 0x20, 0x10, 0xFB, // BIT $2002; BPL * (wait out the PPU warmup)
+            0x2C, 0x02, 0x20, 0x30, 0xFB, // BIT $2002; BMI *
+            0x2C, 0x02, 0x20, 0x10, 0xFB, // BIT $2002; BPL *
+            0xA9, 0x3F, 0x8D, 0x06, 0x20, // LDA #$3F; STA $2006
+            0xA9, 0x00, 0x8D, 0x06, 0x20, // LDA #$00; STA $2006
+            0xA9, 0x16, 0x8D, 0x07, 0x20, // LDA #$16; STA $2007 (backdrop)
+            0x4C, 0x1E, 0x80,             // loop: JMP loop
+        ];
+        raw[16..16 + program.len()].copy_from_slice(&program);
+        raw[16 + 0x3FFC] = 0x00; // Reset vector $8000
+        raw[16 + 0x3FFD] = 0x80;
+
+        let path = env::temp_dir().join("nes-emulator-run-rom-example.nes");
+        std::fs::write(&path, &raw).unwrap();
+        let first = run_rom(&path, 5).unwrap();
+        let second = run_rom(&path, 5).unwrap();
+        let before_program = run_rom(&path, 0).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(first, second);
+        assert_ne!(first, before_program);
+    }
+}
@@ -0,0 +1,219 @@
+// CPU cycles between output bits for each $4010 rate index (NTSC)
+const RATE_TABLE: [u16; 16] = [
+    428, 380, 340, 320, 286, 254, 226, 214, 190, 160, 142, 128, 106, 84, 72, 54,
+];
+
+// Delta modulation channel: the memory reader (sample address/length and the
+// fetch address sequence) and the output unit that plays the fetched bytes
+#[derive(Clone)]
+pub struct Dmc {
+    irq_enabled: bool,
+    loop_sample: bool,
+    rate: u16,
+    timer: u16,
+    sample_buffer: Option<u8>, // Filled by the bus through a DMC DMA
+    shift_register: u8,
+    bits_remaining: u8,
+    silence: bool,
+    sample_address: u16,
+    sample_length: u16,
+    current_address: u16,
+    bytes_remaining: u16,
+    output_level: u8,
+    pub(crate) irq: bool,
+}
+
+impl Default for Dmc {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Dmc {
+    pub fn new() -> Self {
+        // Registers power up as 0, which decodes to $C000 and one byte
+        Dmc {
+            irq_enabled: false,
+            loop_sample: false,
+            rate: RATE_TABLE[0],
+            timer: RATE_TABLE[0],
+            sample_buffer: None,
+            shift_register: 0,
+            bits_remaining: 8,
+            silence: true,
+            sample_address: 0xC000,
+            sample_length: 1,
+            current_address: 0xC000,
+            bytes_remaining: 0,
+            output_level: 0,
+            irq: false,
+        }
+    }
+
+    // $4010: bit 7 enables the IRQ (clearing it acknowledges a pending one),
+    // bit 6 loops the sample and bits 0-3 pick the rate
+    pub fn write_flags(&mut self, data: u8) {
+        self.irq_enabled = data & 0b1000_0000 != 0;
+        self.loop_sample = data & 0b0100_0000 != 0;
+        self.rate = RATE_TABLE[(data & 0x0F) as usize];
+        if !self.irq_enabled {
+            self.irq = false;
+        }
+    }
+
+    // $4011: loads the 7-bit output level directly, the usual way to play PCM
+    pub fn write_output_level(&mut self, data: u8) {
+        self.output_level = data & 0x7F;
+    }
+
+    pub fn output_level(&self) -> u8 {
+        self.output_level
+    }
+
+    // $4012: sample starts at $C000 + A * 64
+    pub fn write_sample_address(&mut self, data: u8) {
+        self.sample_address = 0xC000 | ((data as u16) << 6);
+    }
+
+    // $4013: sample is L * 16 + 1 bytes long
+    pub fn write_sample_length(&mut self, data: u8) {
+        self.sample_length = ((data as u16) << 4) | 1;
+    }
+
+    pub fn restart(&mut self) {
+        self.current_address = self.sample_address;
+        self.bytes_remaining = self.sample_length;
+    }
+
+    pub fn stop(&mut self) {
+        self.bytes_remaining = 0;
+    }
+
+    pub fn bytes_remaining(&self) -> u16 {
+        self.bytes_remaining
+    }
+
+    // The reader wants a byte: the buffer was emptied and the sample goes on
+    pub fn needs_sample(&self) -> bool {
+        self.sample_buffer.is_none() && self.bytes_remaining > 0
+    }
+
+    pub fn load_sample(&mut self, data: u8) {
+        self.sample_buffer = Some(data);
+    }
+
+    // One CPU cycle of the output unit: every `rate` cycles the next bit moves
+    // the output level up or down by 2, and every 8 bits the buffer is emptied
+    // into the shift register
+    pub fn clock(&mut self) {
+        self.timer -= 1;
+        if self.timer > 0 {
+            return;
+        }
+        self.timer = self.rate;
+
+        if !self.silence {
+            if self.shift_register & 1 != 0 {
+                if self.output_level <= 125 {
+                    self.output_level += 2;
+                }
+            } else if self.output_level >= 2 {
+                self.output_level -= 2;
+            }
+        }
+        self.shift_register >>= 1;
+
+        self.bits_remaining -= 1;
+        if self.bits_remaining == 0 {
+            self.bits_remaining = 8;
+            match self.sample_buffer.take() {
+                Some(data) => {
+                    self.shift_register = data;
+                    self.silence = false;
+                }
+                None => self.silence = true,
+            }
+        }
+    }
+
+    // Address of the next sample byte, advancing the reader; None once the
+    // sample has ended. The address wraps from $FFFF to $8000, not $0000
+    pub fn next_fetch_address(&mut self) -> Option<u16> {
+        if self.bytes_remaining == 0 {
+            return None;
+        }
+        let addr = self.current_address;
+        self.current_address = if addr == 0xFFFF { 0x8000 } else { addr + 1 };
+        self.bytes_remaining -= 1;
+
+        if self.bytes_remaining == 0 {
+            if self.loop_sample {
+                self.restart();
+            } else if self.irq_enabled {
+                self.irq = true;
+            }
+        }
+        Some(addr)
+    }
+}
+
+#[cfg(test)]
+mod dmc_tests {
+    use super::*;
+
+    #[test]
+    fn test_fetch_address_wraps_to_8000() {
+        let mut dmc = Dmc::new();
+        dmc.write_sample_address(0xFF); // $FFC0
+        dmc.write_sample_length(0x04); // 65 bytes
+        dmc.restart();
+
+        for offset in 0..64 {
+            assert_eq!(dmc.next_fetch_address(), Some(0xFFC0 + offset));
+        }
+        assert_eq!(dmc.bytes_remaining(), 1);
+        assert_eq!(dmc.next_fetch_address(), Some(0x8000));
+        assert_eq!(dmc.bytes_remaining(), 0);
+        assert_eq!(dmc.next_fetch_address(), None);
+    }
+
+    #[test]
+    fn test_sample_end_raises_irq_or_loops() {
+        let mut dmc = Dmc::new();
+        dmc.write_flags(0b1000_0000);
+        dmc.write_sample_length(0); // 1 byte
+        dmc.restart();
+        dmc.next_fetch_address();
+        assert!(dmc.irq);
+
+        dmc.write_flags(0b0100_0000); // Clearing the IRQ enable acknowledges it
+        assert!(!dmc.irq);
+        dmc.restart();
+        assert_eq!(dmc.next_fetch_address(), Some(0xC000));
+        assert_eq!(dmc.bytes_remaining(), 1); // Looped back to the start
+        assert!(!dmc.irq);
+    }
+
+    #[test]
+    fn test_output_unit_plays_buffer() {
+        let mut dmc = Dmc::new();
+        dmc.write_flags(0x0F); // 54 cycles per bit
+        dmc.write_output_level(0x40);
+        dmc.load_sample(0b0000_0011);
+        assert!(!dmc.needs_sample());
+
+        // The first 8 bits play silence, then the buffer moves to the shifter.
+        // The timer was loaded at power-on, so the new rate starts after a bit
+        for _ in 0..428 + 7 * 54 {
+            dmc.clock();
+        }
+        assert_eq!(dmc.output_level(), 0x40);
+        assert!(dmc.sample_buffer.is_none());
+
+        // Two 1 bits, then 0 bits
+        for _ in 0..3 * 54 {
+            dmc.clock();
+        }
+        assert_eq!(dmc.output_level(), 0x40 + 2 + 2 - 2);
+    }
+}
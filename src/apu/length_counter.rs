@@ -0,0 +1,82 @@
+// Lengths selected by bits 3-7 of the fourth register of each channel
+const LENGTH_TABLE: [u8; 32] = [
+    10, 254, 20, 2, 40, 4, 80, 6, 160, 8, 60, 10, 14, 12, 26, 14, // Indices 0-15
+    12, 16, 24, 18, 48, 20, 96, 22, 192, 24, 72, 26, 16, 28, 32, 30, // Indices 16-31
+];
+
+// Silences a channel after a set number of half frames unless halted
+#[derive(Clone, Default)]
+pub struct LengthCounter {
+    enabled: bool,
+    halted: bool,
+    counter: u8,
+}
+
+impl LengthCounter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Disabling the channel through $4015 also clears the counter
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.counter = 0;
+        }
+    }
+
+    pub fn set_halted(&mut self, halted: bool) {
+        self.halted = halted;
+    }
+
+    // Loads are ignored while the channel is disabled
+    pub fn load(&mut self, index: u8) {
+        if self.enabled {
+            self.counter = LENGTH_TABLE[(index & 0x1F) as usize];
+        }
+    }
+
+    pub fn clock(&mut self) {
+        if !self.halted && self.counter > 0 {
+            self.counter -= 1;
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.counter > 0
+    }
+}
+
+#[cfg(test)]
+mod length_counter_tests {
+    use super::*;
+
+    #[test]
+    fn test_load_and_expire() {
+        let mut length = LengthCounter::new();
+        length.load(3); // Ignored while disabled
+        assert!(!length.is_active());
+
+        length.set_enabled(true);
+        length.load(3); // 2 half frames
+        length.clock();
+        assert!(length.is_active());
+        length.clock();
+        assert!(!length.is_active());
+    }
+
+    #[test]
+    fn test_halt_and_disable() {
+        let mut length = LengthCounter::new();
+        length.set_enabled(true);
+        length.load(0); // 10 half frames
+        length.set_halted(true);
+        for _ in 0..20 {
+            length.clock();
+        }
+        assert!(length.is_active());
+
+        length.set_enabled(false);
+        assert!(!length.is_active());
+    }
+}
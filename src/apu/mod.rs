@@ -0,0 +1,358 @@
+use std::collections::VecDeque;
+
+use crate::apu::{dmc::Dmc, length_counter::LengthCounter};
+
+pub mod dmc;
+pub mod length_counter;
+
+const SAMPLE_BUFFER_CAPACITY: usize = 4096;
+const FRAME_COUNTER_PERIOD: u32 = 29830; // CPU cycles per 4-step sequence
+const FIVE_STEP_PERIOD: u32 = 37282;
+const HALF_FRAME_CYCLES: u32 = 14913; // First half-frame clock of either sequence
+const CPU_CLOCK_HZ: u64 = 1_789_773; // NTSC
+const DEFAULT_SAMPLE_RATE: u32 = 44_100;
+
+#[derive(Clone)]
+pub struct APU {
+    samples: VecDeque<f32>,
+    underruns: u32,
+    sample_rate: u32,
+    sample_clock: u64, // CPU cycles scaled by the sample rate since the last sample
+
+    frame_cycles: u32,
+    five_step_mode: bool, // 5-step sequences never raise the frame IRQ
+    frame_irq_inhibit: bool,
+    frame_irq: bool,
+
+    // Pulse 1, pulse 2, triangle and noise
+    length_counters: [LengthCounter; 4],
+    dmc: Dmc,
+}
+
+impl Default for APU {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl APU {
+    pub fn new() -> Self {
+        APU {
+            samples: VecDeque::with_capacity(SAMPLE_BUFFER_CAPACITY),
+            underruns: 0,
+            sample_rate: DEFAULT_SAMPLE_RATE,
+            sample_clock: 0,
+            frame_cycles: 0,
+            five_step_mode: false,
+            frame_irq_inhibit: false,
+            frame_irq: false,
+            length_counters: Default::default(),
+            dmc: Dmc::new(),
+        }
+    }
+
+    // Advances the frame counter by CPU cycles
+    pub fn tick(&mut self, cycles: u32) {
+        for _ in 0..cycles {
+            self.clock_frame_counter();
+            self.dmc.clock();
+        }
+
+        self.sample_clock += cycles as u64 * self.sample_rate as u64;
+        while self.sample_clock >= CPU_CLOCK_HZ {
+            self.sample_clock -= CPU_CLOCK_HZ;
+            self.push(self.output());
+        }
+    }
+
+    fn clock_frame_counter(&mut self) {
+        self.frame_cycles += 1;
+        let period = if self.five_step_mode {
+            FIVE_STEP_PERIOD
+        } else {
+            FRAME_COUNTER_PERIOD
+        };
+
+        if self.frame_cycles == HALF_FRAME_CYCLES {
+            self.clock_half_frame();
+        }
+        if self.frame_cycles >= period {
+            self.frame_cycles = 0;
+            self.clock_half_frame();
+            if !self.five_step_mode && !self.frame_irq_inhibit {
+                self.frame_irq = true;
+            }
+        }
+    }
+
+    fn clock_half_frame(&mut self) {
+        for length in &mut self.length_counters {
+            length.clock();
+        }
+    }
+
+    // Mixed output level; the DMC is the only channel so far and uses the
+    // linear approximation of the TND mixer
+    fn output(&self) -> f32 {
+        0.00335 * self.dmc.output_level() as f32
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.sample_rate = sample_rate;
+        self.sample_clock = 0;
+    }
+
+    // $4017: bit 7 selects the 5-step sequence, bit 6 inhibits (and clears) the IRQ
+    pub fn write_frame_counter(&mut self, data: u8) {
+        self.five_step_mode = data & 0b1000_0000 != 0;
+        self.frame_irq_inhibit = data & 0b0100_0000 != 0;
+        if self.frame_irq_inhibit {
+            self.frame_irq = false;
+        }
+        self.frame_cycles = 0;
+        // Selecting the 5-step sequence clocks a half frame right away
+        if self.five_step_mode {
+            self.clock_half_frame();
+        }
+    }
+
+    // $4000-$400F: only the length counter halt flags and loads for now
+    pub fn write_register(&mut self, addr: u16, data: u8) {
+        let channel = ((addr - 0x4000) / 4) as usize;
+        match addr & 0b11 {
+            // The triangle uses bit 7 as its halt flag, the others bit 5
+            0 if channel == 2 => self.length_counters[2].set_halted(data & 0b1000_0000 != 0),
+            0 => self.length_counters[channel].set_halted(data & 0b0010_0000 != 0),
+            3 => self.length_counters[channel].load(data >> 3),
+            _ => {}
+        }
+    }
+
+    // $4015 write: bits 0-3 enable the channels, bit 4 starts or stops the
+    // DMC sample; the DMC IRQ is acknowledged either way
+    pub fn write_status(&mut self, data: u8) {
+        for (bit, length) in self.length_counters.iter_mut().enumerate() {
+            length.set_enabled(data & (1 << bit) != 0);
+        }
+        if data & 0b0001_0000 == 0 {
+            self.dmc.stop();
+        } else if self.dmc.bytes_remaining() == 0 {
+            self.dmc.restart();
+        }
+        self.dmc.irq = false;
+    }
+
+    // $4015: channels with a running length counter, an active DMC and both
+    // IRQ flags. Reading acknowledges the frame IRQ, the DMC IRQ stays set
+    pub fn read_status(&mut self) -> u8 {
+        let mut status = 0;
+        for (bit, length) in self.length_counters.iter().enumerate() {
+            if length.is_active() {
+                status |= 1 << bit;
+            }
+        }
+        if self.dmc.bytes_remaining() > 0 {
+            status |= 0b0001_0000;
+        }
+        if self.frame_irq {
+            status |= 0b0100_0000;
+        }
+        if self.dmc.irq {
+            status |= 0b1000_0000;
+        }
+        self.frame_irq = false;
+        status
+    }
+
+    pub fn irq_pending(&self) -> bool {
+        self.frame_irq || self.dmc.irq
+    }
+
+    // $4010-$4013
+    pub fn write_dmc(&mut self, addr: u16, data: u8) {
+        match addr {
+            0x4010 => self.dmc.write_flags(data),
+            0x4011 => self.dmc.write_output_level(data),
+            0x4012 => self.dmc.write_sample_address(data),
+            0x4013 => self.dmc.write_sample_length(data),
+            _ => {}
+        }
+    }
+
+    pub fn dmc(&self) -> &Dmc {
+        &self.dmc
+    }
+
+    pub fn dmc_mut(&mut self) -> &mut Dmc {
+        &mut self.dmc
+    }
+
+    // Once the ring is full the oldest sample is dropped
+    pub fn push(&mut self, sample: f32) {
+        if self.samples.len() == SAMPLE_BUFFER_CAPACITY {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    // Fills `out` with buffered samples and returns how many were written.
+    // Any remainder is padded with silence and counted as an underrun.
+    pub fn drain(&mut self, out: &mut [f32]) -> usize {
+        let count = out.len().min(self.samples.len());
+        for (slot, sample) in out.iter_mut().zip(self.samples.drain(..count)) {
+            *slot = sample;
+        }
+
+        if count < out.len() {
+            out[count..].fill(0.0);
+            self.underruns += 1;
+        }
+        count
+    }
+
+    // Moves every buffered sample to the end of `out`
+    pub fn drain_into(&mut self, out: &mut Vec<f32>) {
+        out.extend(self.samples.drain(..));
+    }
+
+    pub fn buffered(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn underruns(&self) -> u32 {
+        self.underruns
+    }
+}
+
+#[cfg(test)]
+mod apu_tests {
+    use super::*;
+
+    #[test]
+    fn test_drain_partial() {
+        let mut apu = APU::new();
+        for i in 0..10 {
+            apu.push(i as f32);
+        }
+
+        let mut out = [0.0; 4];
+        assert_eq!(apu.drain(&mut out), 4);
+        assert_eq!(out, [0.0, 1.0, 2.0, 3.0]);
+        assert_eq!(apu.buffered(), 6);
+        assert_eq!(apu.underruns(), 0);
+    }
+
+    #[test]
+    fn test_drain_underrun() {
+        let mut apu = APU::new();
+        apu.push(0.5);
+        apu.push(0.25);
+
+        let mut out = [1.0; 4];
+        assert_eq!(apu.drain(&mut out), 2);
+        assert_eq!(out, [0.5, 0.25, 0.0, 0.0]); // Padded with silence
+        assert_eq!(apu.buffered(), 0);
+        assert_eq!(apu.underruns(), 1);
+    }
+
+    #[test]
+    fn test_frame_irq_cleared_by_status_read() {
+        let mut apu = APU::new();
+        apu.tick(FRAME_COUNTER_PERIOD - 1);
+        assert!(!apu.irq_pending());
+
+        apu.tick(1);
+        assert!(apu.irq_pending());
+
+        assert_eq!(apu.read_status() & 0b0100_0000, 0b0100_0000);
+        assert!(!apu.irq_pending());
+        assert_eq!(apu.read_status() & 0b0100_0000, 0);
+    }
+
+    #[test]
+    fn test_status_read_keeps_dmc_irq() {
+        let mut apu = APU::new();
+        apu.dmc.irq = true;
+        apu.tick(FRAME_COUNTER_PERIOD);
+
+        assert_eq!(apu.read_status(), 0b1100_0000);
+        assert!(apu.irq_pending());
+        assert_eq!(apu.read_status(), 0b1000_0000);
+    }
+
+    #[test]
+    fn test_frame_irq_inhibit_and_five_step_mode() {
+        let mut apu = APU::new();
+        apu.write_frame_counter(0b0100_0000);
+        apu.tick(FRAME_COUNTER_PERIOD * 2);
+        assert!(!apu.irq_pending());
+
+        apu.write_frame_counter(0b1000_0000);
+        apu.tick(FRAME_COUNTER_PERIOD * 2);
+        assert!(!apu.irq_pending());
+    }
+
+    #[test]
+    fn test_tick_generates_samples_at_sample_rate() {
+        let mut apu = APU::new();
+        apu.write_dmc(0x4011, 0x7F);
+        apu.tick(CPU_CLOCK_HZ as u32); // One second
+
+        assert_eq!(apu.buffered(), SAMPLE_BUFFER_CAPACITY); // Ring kept the newest
+        let mut out = [0.0; 1];
+        apu.drain(&mut out);
+        assert_eq!(out[0], 0.00335 * 127.0);
+
+        let mut apu = APU::new();
+        apu.set_sample_rate(48_000);
+        apu.tick(CPU_CLOCK_HZ as u32 / 60);
+        assert_eq!(apu.buffered(), 799); // 48000 / 60, less the partial sample
+    }
+
+    #[test]
+    fn test_status_reports_length_counters() {
+        let mut apu = APU::new();
+        apu.write_status(0b0000_0101); // Pulse 1 and triangle
+        apu.write_register(0x4003, 3 << 3); // Pulse 1: 2 half frames
+        apu.write_register(0x4008, 0b1000_0000); // Triangle halted
+        apu.write_register(0x400B, 0);
+        apu.write_register(0x4007, 0); // Pulse 2 is disabled, ignored
+        assert_eq!(apu.read_status() & 0b1111, 0b0101);
+
+        apu.tick(HALF_FRAME_CYCLES);
+        assert_eq!(apu.read_status() & 0b1111, 0b0101);
+        apu.tick(FRAME_COUNTER_PERIOD - HALF_FRAME_CYCLES);
+        assert_eq!(apu.read_status() & 0b1111, 0b0100); // Pulse 1 expired
+
+        apu.write_status(0);
+        assert_eq!(apu.read_status() & 0b1111, 0);
+    }
+
+    #[test]
+    fn test_status_reports_active_dmc() {
+        let mut apu = APU::new();
+        apu.write_dmc(0x4013, 1); // 17 bytes
+        apu.write_status(0b0001_0000);
+        assert_eq!(apu.read_status() & 0b0001_0000, 0b0001_0000);
+
+        apu.write_status(0);
+        assert_eq!(apu.read_status() & 0b0001_0000, 0);
+    }
+
+    #[test]
+    fn test_push_overwrites_oldest_when_full() {
+        let mut apu = APU::new();
+        for i in 0..SAMPLE_BUFFER_CAPACITY + 2 {
+            apu.push(i as f32);
+        }
+
+        let mut out = [0.0; 1];
+        apu.drain(&mut out);
+        assert_eq!(out[0], 2.0);
+        assert_eq!(apu.buffered(), SAMPLE_BUFFER_CAPACITY - 1);
+    }
+}
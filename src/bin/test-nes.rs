@@ -1,6 +1,9 @@
-use std::{env, fs};
+use std::{env, fs, io};
 
-use nes_emulator::{cpu::CPU, mem::rom::Rom};
+use nes_emulator::{
+    cpu::{CPU, trace::Tracer},
+    mem::rom::Rom,
+};
 
 const DEFAULT_FILE_PATH: &str = "nestest.nes";
 
@@ -14,10 +17,13 @@ fn main() {
     let mut cpu = CPU::new();
     cpu.insert_rom(rom);
     cpu.reset();
-    cpu.pc = 0xC000; // Set the program counter to a specific address for testing
+    cpu.set_pc(0xC000); // Set the program counter to a specific address for testing
     cpu.stack = 0xFD; // Set the stack pointer to a specific value for testing
 
+    let mut tracer = Tracer::new(io::stdout().lock());
     cpu.run_with_callback(move |cpu: &mut CPU| {
-        println!("{}", cpu.print_state());
+        tracer
+            .trace(cpu)
+            .expect("Should have been able to write the trace");
     });
 }
@@ -0,0 +1,837 @@
+use std::{fs, path::Path};
+
+use crate::{
+    cpu::{CPU, Registers},
+    joypad::JoypadButton,
+    mem::{
+        bus::BusState,
+        rom::{Mirroring, Rom, RomError},
+    },
+    region::Region,
+    render::{self, frame::Frame},
+    utils::fnv1a,
+};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct StepOutcome {
+    pub cycles: u8,
+    pub dma_cycles: u32, // CPU cycles halted by a DMA before the instruction
+    pub frame_complete: bool,
+    pub nmi: bool, // An NMI was serviced before the instruction
+}
+
+// Save state of the whole machine: CPU registers, RAM, cartridge RAM, PPU and APU
+#[derive(Clone)]
+pub struct Snapshot {
+    registers: Registers,
+    bus: BusState,
+    last_frame_cycles: u32,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum RunError {
+    CycleBudgetExceeded { pc: u16 },
+}
+
+impl std::fmt::Display for RunError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RunError::CycleBudgetExceeded { pc } => {
+                write!(f, "Cycle budget exceeded at PC {:04X}", pc)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RunError {}
+
+type AutosaveHook = Box<dyn FnMut(&[u8])>;
+
+pub struct Console {
+    pub cpu: CPU,
+    pub last_frame_cycles: u32,
+    autosave: Option<AutosaveHook>,
+    frame: Frame, // Last frame rendered by run_frame
+}
+
+impl Console {
+    pub fn from_ines_bytes(raw: &[u8]) -> Result<Console, RomError> {
+        let rom = Rom::new(raw)?;
+        // Only NROM (mapper 0) is wired into the bus at the moment
+        if rom.mapper != 0 {
+            return Err(RomError::UnsupportedMapper(rom.mapper));
+        }
+
+        let mut cpu = CPU::new();
+        cpu.insert_rom(rom); // Builds the PPU from the CHR ROM and mirroring
+        cpu.reset();
+        if let Some(ppu) = cpu.ppu_mut() {
+            ppu.begin_warmup(); // Power-on
+        }
+
+        Ok(Console {
+            cpu,
+            last_frame_cycles: 0,
+            autosave: None,
+            frame: Frame::new(),
+        })
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Console, RomError> {
+        let raw = fs::read(path).map_err(|err| RomError::Io(err.kind()))?;
+        Self::from_ines_bytes(&raw)
+    }
+
+    // Shortcut for tests: an NROM cart running `prg` from $8000. PRG is padded
+    // to 16KB or 32KB and an empty `chr` gives the board CHR RAM
+    #[cfg(test)]
+    pub fn new_nrom_test(prg: Vec<u8>, chr: Vec<u8>) -> Console {
+        assert!(prg.len() <= 0x8000, "NROM PRG is at most 32KB");
+        let prg_size = if prg.len() <= 0x4000 { 0x4000 } else { 0x8000 };
+        let mut prg_rom = prg;
+        prg_rom.resize(prg_size, 0);
+        prg_rom[prg_size - 4] = 0x00; // Reset vector at $FFFC
+        prg_rom[prg_size - 3] = 0x80;
+
+        let chr_is_ram = chr.is_empty();
+        let mut chr_rom = chr;
+        chr_rom.resize(chr_rom.len().max(0x2000), 0);
+
+        let mut cpu = CPU::new();
+        cpu.insert_rom(Rom {
+            prg_rom,
+            chr_rom,
+            chr_is_ram,
+            mapper: 0,
+            screen_mirroring: Mirroring::Horizontal,
+            has_battery: false,
+            trainer: None,
+            prg_ram_size: 0x2000,
+        });
+        cpu.reset();
+
+        Console {
+            cpu,
+            last_frame_cycles: 0,
+            autosave: None,
+            frame: Frame::new(),
+        }
+    }
+
+    // Switches between NTSC and PAL timing: scanlines per frame and the
+    // CPU:PPU clock ratio
+    pub fn set_region(&mut self, region: Region) {
+        self.cpu.bus.set_region(region);
+    }
+
+    // Sets all eight buttons of controller 1 from a byte laid out as
+    // A, B, Select, Start, Up, Down, Left, Right from bit 0 to bit 7
+    pub fn controller_state_from_bits(&mut self, bits: u8) {
+        self.cpu
+            .bus
+            .joypad1_mut()
+            .set_buttons(JoypadButton::from_bits_truncate(bits));
+    }
+
+    pub fn is_battery_backed(&self) -> bool {
+        self.cpu
+            .bus
+            .rom()
+            .is_some_and(|rom| rom.is_battery_backed())
+    }
+
+    // Contents of PRG RAM for save files, only for battery-backed carts
+    pub fn export_sram(&self) -> Option<Vec<u8>> {
+        if !self.is_battery_backed() {
+            return None;
+        }
+        Some(self.cpu.bus.prg_ram().to_vec())
+    }
+
+    // Called with the SRAM contents at most once per frame, after a frame in
+    // which the game wrote to battery RAM
+    pub fn set_autosave_hook<F>(&mut self, hook: F)
+    where
+        F: FnMut(&[u8]) + 'static,
+    {
+        self.autosave = Some(Box::new(hook));
+    }
+
+    pub fn insert_sram(&mut self, sram: &[u8]) {
+        self.cpu.bus.load_prg_ram(sram);
+    }
+
+    pub fn insert_sram_and_run(&mut self, sram: &[u8], frames: u32, frame: &mut Frame) {
+        self.insert_sram(sram);
+        self.run_frames(frames, frame);
+    }
+
+    fn flush_autosave(&mut self) {
+        if !self.cpu.bus.poll_prg_ram_dirty() {
+            return;
+        }
+        if let Some(sram) = self.export_sram()
+            && let Some(hook) = self.autosave.as_mut()
+        {
+            hook(&sram);
+        }
+    }
+
+    pub fn save_state(&self) -> Snapshot {
+        Snapshot {
+            registers: self.cpu.registers(),
+            bus: self.cpu.bus.save_state(),
+            last_frame_cycles: self.last_frame_cycles,
+        }
+    }
+
+    pub fn load_state(&mut self, snapshot: &Snapshot) {
+        self.cpu.set_registers(snapshot.registers);
+        self.cpu.bus.load_state(&snapshot.bus);
+        self.last_frame_cycles = snapshot.last_frame_cycles;
+    }
+
+    pub fn enable_coverage(&mut self) {
+        self.cpu.enable_coverage();
+    }
+
+    pub fn coverage(&self) -> &[bool] {
+        self.cpu.coverage()
+    }
+
+    pub fn drain_audio(&mut self, out: &mut [f32]) -> usize {
+        self.cpu.bus.apu_mut().drain(out)
+    }
+
+    // Runs one frame and appends the audio samples generated while it ran
+    pub fn run_frame_with_audio(&mut self, frame: &mut Frame, audio: &mut Vec<f32>) {
+        self.run_frames(1, frame);
+        self.cpu.bus.apu_mut().drain_into(audio);
+    }
+
+    // Runs one frame and renders it into the console's own frame buffer
+    pub fn run_frame(&mut self) {
+        let mut frame = std::mem::take(&mut self.frame);
+        self.run_frames(1, &mut frame);
+        self.frame = frame;
+    }
+
+    // RGB24 pixels of the last frame rendered by run_frame
+    pub fn frame_buffer(&self) -> &[u8] {
+        &self.frame.data
+    }
+
+    // Runs until the PPU has completed `n` frames and renders the last one
+    pub fn run_frames(&mut self, n: u32, frame: &mut Frame) {
+        let mut frames = 0;
+        let mut cycles = 0;
+        while frames < n {
+            cycles += self.cpu.step();
+            if self.cpu.bus.poll_frame_complete() {
+                self.flush_autosave();
+                frames += 1;
+                self.last_frame_cycles = cycles;
+                cycles = 0;
+            }
+        }
+
+        if let Some(ppu) = self.cpu.ppu() {
+            render::render(ppu, frame);
+        }
+    }
+
+    // Stable hash of the current PPU state rendered as RGB, for comparing runs
+    pub fn frame_hash(&self) -> u64 {
+        let mut frame = Frame::new();
+        if let Some(ppu) = self.cpu.ppu() {
+            render::render(ppu, &mut frame);
+        }
+        fnv1a(frame.data)
+    }
+
+    // Runs whole instructions until at least `budget` CPU cycles have elapsed
+    // and returns how many actually ran
+    pub fn run_cycles(&mut self, budget: u64) -> u64 {
+        let start = self.cpu.bus.cpu_cycles();
+        while self.cpu.bus.cpu_cycles() - start < budget {
+            self.step_instruction();
+        }
+        self.cpu.bus.cpu_cycles() - start
+    }
+
+    // Steps until the next instruction would execute from `target`, for test
+    // ROMs that signal completion by reaching a known address
+    pub fn run_until_pc(&mut self, target: u16, max_cycles: u64) -> Result<(), RunError> {
+        let start = self.cpu.bus.cpu_cycles();
+        while self.cpu.pc() != target {
+            if self.cpu.bus.cpu_cycles() - start >= max_cycles {
+                return Err(RunError::CycleBudgetExceeded { pc: self.cpu.pc() });
+            }
+            self.step_instruction();
+        }
+        Ok(())
+    }
+
+    // Executes one instruction (plus a pending NMI) and reports what happened.
+    // A DMA stall left by the previous instruction is run out first
+    pub fn step_instruction(&mut self) -> StepOutcome {
+        let dma_cycles = if self.cpu.bus.dma_in_progress() {
+            self.cpu.step()
+        } else {
+            0
+        };
+        let nmi = self.cpu.ppu().is_some_and(|ppu| ppu.get_nmi_flag());
+        let cycles = self.cpu.step() as u8;
+        let frame_complete = self.cpu.bus.poll_frame_complete();
+        if frame_complete {
+            self.flush_autosave();
+        }
+
+        StepOutcome {
+            cycles,
+            dma_cycles,
+            frame_complete,
+            nmi,
+        }
+    }
+
+    // Steps the CPU until the PPU enters VBlank (scanline 241), then renders
+    // into `frame` if one is given
+    pub fn run_until_vblank(&mut self, frame: Option<&mut Frame>) {
+        while !self.cpu.bus.poll_frame_complete() {
+            self.cpu.step();
+        }
+        self.flush_autosave();
+
+        if let Some(frame) = frame
+            && let Some(ppu) = self.cpu.ppu()
+        {
+            render::render(ppu, frame);
+        }
+    }
+
+    // Renders the current PPU state and encodes it as a 256x240 RGB PNG
+    #[cfg(feature = "png")]
+    pub fn screenshot_png(&mut self) -> Vec<u8> {
+        let mut frame = Frame::new();
+        if let Some(ppu) = self.cpu.ppu() {
+            render::render(ppu, &mut frame);
+        }
+
+        let mut png_data = Vec::new();
+        let mut encoder =
+            png::Encoder::new(&mut png_data, Frame::WIDTH as u32, Frame::HIGHT as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        // Encoding into memory cannot hit I/O errors
+        let mut writer = encoder.write_header().expect("PNG header encoding failed");
+        writer
+            .write_image_data(&frame.data)
+            .expect("PNG image encoding failed");
+        writer.finish().expect("PNG encoding failed");
+        png_data
+    }
+
+    // Debugger/cheat access that skips the side effects of real bus cycles
+    pub fn peek(&self, addr: u16) -> u8 {
+        self.cpu.bus.peek_u8(addr)
+    }
+
+    pub fn poke(&mut self, addr: u16, value: u8) {
+        self.cpu.bus.poke_u8(addr, value);
+    }
+
+    // Base nametable selected by PPUCTRL, before scrolling and mirroring
+    pub fn active_nametable(&self) -> u8 {
+        self.cpu.ppu().map_or(0, |ppu| ppu.base_nametable())
+    }
+
+    // How the four logical nametables fold onto the 2KB of VRAM
+    pub fn mirroring(&self) -> Option<Mirroring> {
+        self.cpu.ppu().map(|ppu| ppu.mirroring)
+    }
+
+    pub fn tick_ppu(&mut self, dots: u32) -> bool {
+        self.cpu.bus.tick_ppu(dots)
+    }
+}
+
+#[cfg(test)]
+mod console_tests {
+    use super::*;
+    use crate::mem::Memory;
+
+    // Helper function to create a 16KB NROM image with the given reset vector
+    fn create_nrom_data(reset_vector: u16) -> Vec<u8> {
+        let mut rom_data = Rom::create_rom_data(1, 1, 0x00, 0x00, false);
+        let [lo, hi] = reset_vector.to_le_bytes();
+        rom_data[16 + 0x3FFC] = lo;
+        rom_data[16 + 0x3FFD] = hi;
+        rom_data
+    }
+
+    // Helper function to create an NROM image running `program` from 0x8000
+    fn create_program_data(program: &[u8]) -> Vec<u8> {
+        let mut rom_data = create_nrom_data(0x8000);
+        rom_data[16..16 + program.len()].copy_from_slice(program);
+        rom_data
+    }
+
+    #[test]
+    fn test_from_ines_bytes_resets_cpu() {
+        let console = Console::from_ines_bytes(&create_nrom_data(0x8123)).unwrap();
+
+        assert_eq!(console.cpu.pc(), 0x8123);
+    }
+
+    #[test]
+    fn test_from_ines_bytes_starts_ppu_warmup() {
+        let mut console = Console::from_ines_bytes(&create_nrom_data(0x8000)).unwrap();
+
+        // PPUCTRL writes are dropped until the warmup has run out
+        console.cpu.mem_write_u8(0x2000, 0b1000_0000);
+        assert!(!console.tick_ppu(241 * 341));
+
+        console.tick_ppu(29658 * 3); // Ends just before scanline 240
+        console.cpu.mem_write_u8(0x2000, 0b1000_0000);
+        assert!(console.tick_ppu(2 * 341));
+    }
+
+    #[test]
+    fn test_from_ines_bytes_invalid_tag() {
+        let mut rom_data = create_nrom_data(0x8000);
+        rom_data[0] = 0x00;
+
+        let result = Console::from_ines_bytes(&rom_data);
+        assert_eq!(result.err(), Some(RomError::InvalidTag));
+    }
+
+    #[test]
+    fn test_from_ines_bytes_unsupported_mapper() {
+        let rom_data = Rom::create_rom_data(1, 1, 0x10, 0x00, false);
+
+        let result = Console::from_ines_bytes(&rom_data);
+        assert_eq!(result.err(), Some(RomError::UnsupportedMapper(1)));
+    }
+
+    #[test]
+    fn test_new_nrom_test() {
+        // LDA #$42; STA $00; then BRK from the zero padding
+        let mut console = Console::new_nrom_test(vec![0xA9, 0x42, 0x85, 0x00], vec![]);
+        assert_eq!(console.cpu.pc(), 0x8000);
+
+        console.cpu.run();
+
+        assert_eq!(console.cpu.mem_read_u8(0x0000), 0x42);
+        assert_eq!(console.cpu.pc(), 0x8005);
+        let ppu = console.cpu.ppu().unwrap();
+        assert_eq!(ppu.chr_rom.len(), 0x2000);
+        assert!(ppu.chr_is_ram);
+    }
+
+    #[test]
+    #[should_panic(expected = "NROM PRG is at most 32KB")]
+    fn test_new_nrom_test_rejects_oversized_prg() {
+        Console::new_nrom_test(vec![0xEA; 0x8001], vec![]);
+    }
+
+    #[test]
+    fn test_tick_ppu_reaches_vblank_without_cpu() {
+        let mut console = Console::from_ines_bytes(&create_nrom_data(0x8000)).unwrap();
+        console.cpu.ppu_mut().unwrap().end_warmup();
+        console.cpu.mem_write_u8(0x2000, 0b1000_0000); // Enable NMI on VBlank
+
+        // One dot short of scanline 241 nothing is pending yet
+        assert!(!console.tick_ppu(241 * 341 - 1));
+        assert!(console.tick_ppu(1));
+
+        // The CPU did not execute anything
+        assert_eq!(console.cpu.pc(), 0x8000);
+    }
+
+    #[test]
+    fn test_tick_ppu_no_nmi_when_disabled() {
+        let mut console = Console::from_ines_bytes(&create_nrom_data(0x8000)).unwrap();
+
+        assert!(!console.tick_ppu(241 * 341));
+        assert_eq!(console.cpu.pc(), 0x8000);
+    }
+
+    #[test]
+    fn test_run_frames() {
+        // JMP $8000
+        let rom_data = create_program_data(&[0x4C, 0x00, 0x80]);
+        let mut console = Console::from_ines_bytes(&rom_data).unwrap();
+        let mut frame = Frame::new();
+
+        let mut total_cycles = 0;
+        for _ in 0..2 {
+            console.run_frames(1, &mut frame);
+            total_cycles += console.last_frame_cycles;
+        }
+
+        // The first frame from power-on only runs up to VBlank (241 scanlines)
+        assert!(total_cycles.abs_diff(241 * 341 / 3 + 29780) <= 6);
+        // A steady-state frame is 262 * 341 / 3 CPU cycles
+        assert!(console.last_frame_cycles.abs_diff(29780) <= 3);
+
+        console.run_frames(2, &mut frame);
+        assert!(console.last_frame_cycles.abs_diff(29780) <= 3);
+    }
+
+    #[test]
+    fn test_run_cycles() {
+        // LDX #$00; INX; JMP $8002
+        let rom_data = create_program_data(&[0xA2, 0x00, 0xE8, 0x4C, 0x02, 0x80]);
+        let mut console = Console::from_ines_bytes(&rom_data).unwrap();
+
+        let cycles = console.run_cycles(10_000);
+
+        // The longest instruction is 7 cycles
+        assert!(cycles >= 10_000);
+        assert!(cycles < 10_000 + 7);
+    }
+
+    #[test]
+    fn test_step_instruction() {
+        // LDA #$80; STA $2000; JMP $8005; NMI handler at $8008: RTI
+        let mut rom_data =
+            create_program_data(&[0xA9, 0x80, 0x8D, 0x00, 0x20, 0x4C, 0x05, 0x80, 0x40]);
+        rom_data[16 + 0x3FFA] = 0x08; // NMI vector
+        rom_data[16 + 0x3FFB] = 0x80;
+        let mut console = Console::from_ines_bytes(&rom_data).unwrap();
+        console.cpu.ppu_mut().unwrap().end_warmup();
+
+        // The first frame from power-on only runs up to VBlank
+        while !console.step_instruction().frame_complete {}
+
+        let mut cycles = 0;
+        let mut nmis = 0;
+        loop {
+            let outcome = console.step_instruction();
+            cycles += outcome.cycles as u32;
+            nmis += outcome.nmi as u32;
+            if outcome.frame_complete {
+                break;
+            }
+        }
+
+        assert!(cycles.abs_diff(29780) <= 7);
+        assert_eq!(nmis, 1);
+    }
+
+    #[test]
+    fn test_run_until_vblank() {
+        // JMP $8000
+        let rom_data = create_program_data(&[0x4C, 0x00, 0x80]);
+        let mut console = Console::from_ines_bytes(&rom_data).unwrap();
+
+        console.run_until_vblank(None);
+        let ppu = console.cpu.ppu().unwrap();
+        assert!(ppu.in_vblank());
+        assert_eq!(ppu.scanline(), 241);
+
+        let mut frame = Frame::new();
+        console.run_until_vblank(Some(&mut frame));
+        assert!(console.cpu.ppu().unwrap().in_vblank());
+        assert_eq!(console.cpu.ppu().unwrap().scanline(), 241);
+    }
+
+    #[test]
+    fn test_controller_state_from_bits() {
+        let mut console = Console::from_ines_bytes(&create_nrom_data(0x8000)).unwrap();
+        console.controller_state_from_bits(0b1000_0001);
+
+        assert_eq!(
+            console.cpu.bus.joypad1().buttons(),
+            JoypadButton::BUTTON_A | JoypadButton::RIGHT
+        );
+
+        // A new state replaces the previous one entirely
+        console.controller_state_from_bits(0b0000_1000);
+        assert_eq!(console.cpu.bus.joypad1().buttons(), JoypadButton::START);
+    }
+
+    #[test]
+    fn test_export_sram() {
+        let mut rom_data = create_nrom_data(0x8000);
+        rom_data[7] |= 0b0000_0010; // Battery flag
+        let mut console = Console::from_ines_bytes(&rom_data).unwrap();
+        console.cpu.mem_write_u8(0x6000, 0x5A);
+
+        assert!(console.is_battery_backed());
+        let sram = console.export_sram().unwrap();
+        assert_eq!(sram.len(), 0x2000);
+        assert_eq!(sram[0], 0x5A);
+
+        let console = Console::from_ines_bytes(&create_nrom_data(0x8000)).unwrap();
+        assert!(!console.is_battery_backed());
+        assert_eq!(console.export_sram(), None);
+    }
+
+    #[test]
+    fn test_autosave_hook() {
+        use std::{cell::RefCell, rc::Rc};
+
+        // LDA $6000; ADC #$01; STA $6000; JMP $8008
+        let mut rom_data = create_program_data(&[
+            0xAD, 0x00, 0x60, 0x69, 0x01, 0x8D, 0x00, 0x60, 0x4C, 0x08, 0x80,
+        ]);
+        rom_data[7] |= 0b0000_0010; // Battery flag
+        let mut console = Console::from_ines_bytes(&rom_data).unwrap();
+
+        let saves = Rc::new(RefCell::new(Vec::new()));
+        let hook_saves = Rc::clone(&saves);
+        console.set_autosave_hook(move |sram| hook_saves.borrow_mut().push(sram.to_vec()));
+
+        let mut frame = Frame::new();
+        console.insert_sram_and_run(&[0x41], 3, &mut frame);
+
+        // One save for the frame with the write, none for the idle frames
+        let saves = saves.borrow();
+        assert_eq!(saves.len(), 1);
+        assert_eq!(saves[0].len(), 0x2000);
+        assert_eq!(saves[0][0], 0x42);
+    }
+
+    #[test]
+    fn test_coverage() {
+        // LDX #$00; BEQ +1; INX; INX; JMP $8006
+        let rom_data = create_program_data(&[0xA2, 0x00, 0xF0, 0x01, 0xE8, 0xE8, 0x4C, 0x06, 0x80]);
+        let mut console = Console::from_ines_bytes(&rom_data).unwrap();
+        assert!(console.coverage().is_empty());
+
+        console.enable_coverage();
+        console.run_cycles(20);
+
+        let coverage = console.coverage();
+        assert_eq!(coverage.len(), 0x8000);
+        let executed: Vec<usize> = (0..coverage.len()).filter(|&i| coverage[i]).collect();
+        // The skipped INX at $8004 and operand bytes are never opcode starts
+        assert_eq!(executed, vec![0x0000, 0x0002, 0x0005, 0x0006]);
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn test_screenshot_png() {
+        let mut console = Console::new_nrom_test(vec![], vec![]);
+        console.cpu.ppu_mut().unwrap().palette_table[0] = 0x16;
+
+        let png_data = console.screenshot_png();
+
+        let decoder = png::Decoder::new(std::io::Cursor::new(png_data));
+        let mut reader = decoder.read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size().unwrap()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+
+        assert_eq!((info.width, info.height), (256, 240));
+        assert_eq!(info.color_type, png::ColorType::Rgb);
+        let (r, g, b) = crate::render::palette::SYSTEM_PALLETE[0x16];
+        let base = (120 * 256 + 128) * 3;
+        assert_eq!(&pixels[base..base + 3], &[r, g, b]);
+    }
+
+    #[test]
+    fn test_drain_audio() {
+        let mut console = Console::from_ines_bytes(&create_nrom_data(0x8000)).unwrap();
+        for _ in 0..8 {
+            console.cpu.bus.apu_mut().push(0.5);
+        }
+
+        let mut out = [0.0; 5];
+        assert_eq!(console.drain_audio(&mut out), 5);
+        assert_eq!(console.cpu.bus.apu().buffered(), 3);
+
+        assert_eq!(console.drain_audio(&mut out), 3);
+        assert_eq!(console.cpu.bus.apu().underruns(), 1);
+    }
+
+    #[test]
+    fn test_run_frame_with_audio() {
+        #[rustfmt::skip]
+        let program = vec![
+            0xA9, 0x7F, 0x8D, 0x11, 0x40, // loop: LDA #$7F; STA $4011
+            0xA2, 0x00, 0xCA, 0xD0, 0xFD, //       LDX #$00; wait: DEX; BNE wait
+            0xA9, 0x00, 0x8D, 0x11, 0x40, //       LDA #$00; STA $4011
+            0xA2, 0x00, 0xCA, 0xD0, 0xFD, //       LDX #$00; wait: DEX; BNE wait
+            0x4C, 0x00, 0x80,             //       JMP loop
+        ];
+        let mut console = Console::new_nrom_test(program, vec![]);
+        let mut frame = Frame::new();
+        console.run_frames(1, &mut frame);
+        console.drain_audio(&mut [0.0; 4096]); // Skip the partial first frame
+
+        let mut audio = Vec::new();
+        console.run_frame_with_audio(&mut frame, &mut audio);
+
+        let expected = (console.cpu.bus.apu().sample_rate() / 60) as usize;
+        assert!(
+            audio.len().abs_diff(expected) <= 5,
+            "{} samples",
+            audio.len()
+        );
+        assert!(audio.contains(&0.0));
+        assert!(audio.iter().any(|&sample| sample > 0.4)); // Square wave
+        assert_eq!(console.cpu.bus.apu().buffered(), 0);
+    }
+
+    #[test]
+    fn test_from_file() {
+        let path = std::env::temp_dir().join("nes-emulator-test-from-file.nes");
+        fs::write(&path, create_nrom_data(0x8123)).unwrap();
+        let console = Console::from_file(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(console.cpu.pc(), 0x8123);
+
+        let result = Console::from_file(&path);
+        assert_eq!(
+            result.err(),
+            Some(RomError::Io(std::io::ErrorKind::NotFound))
+        );
+    }
+
+    #[test]
+    fn test_frame_hash() {
+        let mut console = Console::new_nrom_test(vec![0x4C, 0x00, 0x80], vec![]);
+        let blank = console.frame_hash();
+        assert_eq!(console.frame_hash(), blank);
+
+        console.cpu.ppu_mut().unwrap().palette_table[0] = 0x16;
+        assert_ne!(console.frame_hash(), blank);
+    }
+
+    #[test]
+    fn test_set_region_pal_frame_length() {
+        let mut console = Console::new_nrom_test(vec![0x4C, 0x00, 0x80], vec![]);
+        let mut frame = Frame::new();
+        console.run_frames(2, &mut frame);
+        assert!(console.last_frame_cycles.abs_diff(29781) <= 3);
+
+        console.set_region(Region::Pal);
+        console.run_frames(2, &mut frame);
+        // 312 scanlines of 341 dots at 3.2 dots per cycle
+        assert!(console.last_frame_cycles.abs_diff(33248) <= 3);
+    }
+
+    #[test]
+    fn test_run_until_pc() {
+        // INX; INX; JMP $8010; ... $8010: INY; JMP $8010
+        let mut prg = vec![0xE8, 0xE8, 0x4C, 0x10, 0x80];
+        prg.resize(0x10, 0xEA);
+        prg.extend([0xC8, 0x4C, 0x10, 0x80]);
+        let mut console = Console::new_nrom_test(prg, vec![]);
+
+        assert_eq!(console.run_until_pc(0x8010, 100), Ok(()));
+        assert_eq!(console.cpu.reg_x, 2);
+        assert_eq!(console.cpu.reg_y, 0);
+
+        // $8005 is never reached again
+        assert_eq!(
+            console.run_until_pc(0x8005, 1000),
+            Err(RunError::CycleBudgetExceeded { pc: 0x8010 })
+        );
+    }
+
+    #[test]
+    fn test_step_instruction_reports_oam_dma() {
+        // LDA #$02; STA $4014; JMP $8005
+        let prg = vec![0xA9, 0x02, 0x8D, 0x14, 0x40, 0x4C, 0x05, 0x80];
+        let mut console = Console::new_nrom_test(prg.clone(), vec![]);
+
+        console.step_instruction();
+        assert_eq!(console.step_instruction().dma_cycles, 0);
+
+        let outcome = console.step_instruction(); // JMP after the DMA
+        assert!((513..=514).contains(&outcome.dma_cycles));
+        assert_eq!(outcome.cycles, 3);
+
+        // The stall counts against the budget instead of wrapping in a u8
+        let mut console = Console::new_nrom_test(prg, vec![]);
+        let start = console.cpu.bus.cpu_cycles();
+        assert_eq!(
+            console.run_until_pc(0x9000, 20),
+            Err(RunError::CycleBudgetExceeded { pc: 0x8005 })
+        );
+        let elapsed = console.cpu.bus.cpu_cycles() - start;
+        assert!((2 + 4 + 513 + 3..=2 + 4 + 514 + 3).contains(&elapsed));
+    }
+
+    #[test]
+    fn test_save_and_load_state() {
+        #[rustfmt::skip]
+        let program = vec![
+            0xA9, 0x08, 0x8D, 0x01, 0x20, // LDA #$08; STA $2001 (background on)
+            0xA2, 0x00,                   // LDX #$00
+            0xA9, 0x3F, 0x8D, 0x06, 0x20, // loop: LDA #$3F; STA $2006
+            0xA9, 0x00, 0x8D, 0x06, 0x20, //       LDA #$00; STA $2006
+            0x8A, 0x29, 0x3F,             //       TXA; AND #$3F
+            0x8D, 0x07, 0x20,             //       STA $2007 (backdrop color)
+            0xE8, 0x4C, 0x07, 0x80,       //       INX; JMP loop
+        ];
+        let mut console = Console::new_nrom_test(program, vec![]);
+        let mut frame = Frame::new();
+        console.run_frames(2, &mut frame);
+
+        let snapshot = console.save_state();
+        let mut expected = Frame::new();
+        console.run_until_vblank(Some(&mut expected));
+        let registers = console.cpu.registers();
+        let cycles = console.cpu.bus.cpu_cycles();
+        let dots = console.cpu.bus.ppu_dots();
+
+        console.run_frames(3, &mut frame);
+        console.load_state(&snapshot);
+        let mut restored = Frame::new();
+        console.run_until_vblank(Some(&mut restored));
+
+        assert_eq!(restored.data, expected.data);
+        assert_eq!(console.cpu.registers(), registers);
+        assert_eq!(console.cpu.bus.cpu_cycles(), cycles);
+        assert_eq!(console.cpu.bus.ppu_dots(), dots);
+    }
+
+    #[test]
+    fn test_peek_and_poke() {
+        let mut console = Console::new_nrom_test(vec![], vec![]);
+
+        console.poke(0x0042, 0x99);
+        assert_eq!(console.peek(0x0042), 0x99);
+        assert_eq!(console.cpu.mem_read_u8(0x0842), 0x99); // RAM mirror
+
+        console.poke(0x8000, 0xEA);
+        assert_eq!(console.peek(0x8000), 0xEA);
+
+        console.tick_ppu(241 * 341 + 1);
+        assert!(console.cpu.ppu().unwrap().in_vblank());
+        console.peek(0x2002);
+        assert!(console.cpu.ppu().unwrap().in_vblank()); // Not acknowledged
+    }
+
+    #[test]
+    fn test_active_nametable() {
+        let mut console = Console::from_ines_bytes(&create_nrom_data(0x8000)).unwrap();
+        console.cpu.ppu_mut().unwrap().end_warmup();
+        assert_eq!(console.active_nametable(), 0);
+        assert_eq!(console.mirroring(), Some(Mirroring::Horizontal));
+
+        console.cpu.mem_write_u8(0x2000, 0b1000_0010);
+        assert_eq!(console.active_nametable(), 2);
+    }
+
+    #[test]
+    fn test_frame_buffer() {
+        let mut console = Console::new_nrom_test(vec![0x4C, 0x00, 0x80], vec![]); // JMP $8000
+        assert!(console.frame_buffer().iter().all(|&byte| byte == 0));
+
+        console.cpu.ppu_mut().unwrap().palette_table[0] = 0x16;
+        console.run_frame();
+
+        assert_eq!(console.frame_buffer().len(), 256 * 240 * 3);
+        let (r, g, b) = crate::render::palette::SYSTEM_PALLETE[0x16];
+        assert_eq!(&console.frame_buffer()[..3], &[r, g, b]);
+        assert_eq!(
+            console.frame_hash(),
+            fnv1a(console.frame_buffer().iter().copied())
+        );
+    }
+}
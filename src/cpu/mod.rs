@@ -1,17 +1,21 @@
 pub mod opcode;
 pub mod opcode_table;
+pub mod trace;
 
-use std::fmt::Debug;
+use std::fmt::{self, Debug};
 
 use crate::{
     cpu::opcode::{AddressingMode, OP},
     mem::{Memory, bus::Bus, rom::Rom},
+    ppu::PPU,
     utils::set_bit,
 };
 
 const INIT_STACK_POINTER: u8 = 0xFF;
 const PC_START_ADDRESS: u16 = 0xFFFC;
 
+type CycleHook = Box<dyn FnMut(&mut Bus)>;
+
 pub struct CPU {
     pub pc: u16,
     pub status: u8,
@@ -20,8 +24,52 @@ pub struct CPU {
     pub reg_x: u8,
     pub reg_y: u8,
     pub bus: Bus,
+    // The 2A03 has the BCD circuitry disconnected, so ADC/SBC ignore the
+    // decimal flag unless this is enabled to emulate a stock NMOS 6502
+    pub bcd_enabled: bool,
+    // Debug aid: report stack pointer wrap-around instead of wrapping silently
+    pub strict_stack: bool,
+    // BRK halts `run` by default; when enabled it runs the full interrupt
+    // sequence through the IRQ/BRK vector instead
+    pub brk_interrupts: bool,
+    error: Option<CpuError>,
+    last_opcode: u8,
+    last_opcode_pc: u16,
+    coverage: Option<Vec<bool>>, // Opcode starts executed in $8000-$FFFF, when enabled
+    page_crossed: bool,          // Whether the last indexed address resolution crossed a page
+    extra_cycles: u8,            // Cycles the current instruction spent beyond its base count
+    ticked_cycles: u8,           // Cycles the current instruction already ticked on the bus
+    // Called after every CPU cycle while `run_with_cycle_callback` runs
+    cycle_hook: Option<CycleHook>,
 }
 
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Registers {
+    pub a: u8,
+    pub x: u8,
+    pub y: u8,
+    pub pc: u16,
+    pub status: u8,
+    pub stack: u8,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CpuError {
+    StackOverflow,
+    StackUnderflow,
+}
+
+impl fmt::Display for CpuError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CpuError::StackOverflow => write!(f, "Stack overflow: push with SP at 0x00"),
+            CpuError::StackUnderflow => write!(f, "Stack underflow: pull with SP at 0xFF"),
+        }
+    }
+}
+
+impl std::error::Error for CpuError {}
+
 impl Default for CPU {
     fn default() -> Self {
         Self::new()
@@ -30,6 +78,11 @@ impl Default for CPU {
 
 impl CPU {
     pub fn new() -> Self {
+        Self::with_bus(Bus::new())
+    }
+
+    // For a bus that is already set up, e.g. with a cartridge or custom devices
+    pub fn with_bus(bus: Bus) -> Self {
         CPU {
             pc: 0,
             status: 0b00100100,
@@ -37,9 +90,21 @@ impl CPU {
             reg_x: 0,
             reg_y: 0,
             stack: INIT_STACK_POINTER,
-            bus: Bus::new(),
+            bus,
+            bcd_enabled: false,
+            strict_stack: false,
+            brk_interrupts: false,
+            error: None,
+            last_opcode: 0,
+            last_opcode_pc: 0,
+            coverage: None,
+            page_crossed: false,
+            extra_cycles: 0,
+            ticked_cycles: 0,
+            cycle_hook: None,
         }
     }
+
     pub fn load_and_run(&mut self, ram: Vec<u8>) {
         self.load(ram);
         self.reset();
@@ -53,7 +118,12 @@ impl CPU {
 
     pub fn load_at(&mut self, program: Vec<u8>, start_address: usize) {
         for i in 0..(program.len() as u16) {
-            self.mem_write_u8((start_address as u16) + i, program[i as usize]);
+            let addr = (start_address as u16) + i;
+            if addr >= 0x8000 {
+                self.bus.write_prg_rom(addr, program[i as usize]);
+            } else {
+                self.mem_write_u8(addr, program[i as usize]);
+            }
         }
         self.reset();
     }
@@ -62,6 +132,77 @@ impl CPU {
         self.bus.insert_rom(rom);
     }
 
+    pub fn ppu(&self) -> Option<&PPU> {
+        self.bus.ppu()
+    }
+
+    pub fn ppu_mut(&mut self) -> Option<&mut PPU> {
+        self.bus.ppu_mut()
+    }
+
+    // Starts recording which PRG addresses are executed as opcode starts
+    pub fn enable_coverage(&mut self) {
+        self.coverage.get_or_insert_with(|| vec![false; 0x8000]);
+    }
+
+    // One entry per address from $8000, empty while coverage is disabled
+    pub fn coverage(&self) -> &[bool] {
+        self.coverage.as_deref().unwrap_or(&[])
+    }
+
+    pub fn last_opcode(&self) -> u8 {
+        self.last_opcode
+    }
+
+    pub fn last_opcode_pc(&self) -> u16 {
+        self.last_opcode_pc
+    }
+
+    pub fn registers(&self) -> Registers {
+        Registers {
+            a: self.reg_a,
+            x: self.reg_x,
+            y: self.reg_y,
+            pc: self.pc,
+            status: self.status,
+            stack: self.stack,
+        }
+    }
+
+    // Prefer these over the `pc` field, which may stop being public
+    pub fn pc(&self) -> u16 {
+        self.pc
+    }
+
+    // The next step() fetches its opcode from `pc`
+    pub fn set_pc(&mut self, pc: u16) {
+        self.pc = pc;
+    }
+
+    pub fn set_registers(&mut self, registers: Registers) {
+        self.reg_a = registers.a;
+        self.reg_x = registers.x;
+        self.reg_y = registers.y;
+        self.pc = registers.pc;
+        self.status = registers.status;
+        self.stack = registers.stack;
+    }
+
+    // Side-effect-free window of the address space; wraps past $FFFF
+    pub fn memory_dump(&self, start: u16, len: usize) -> Vec<u8> {
+        (0..len)
+            .map(|i| self.bus.peek_u8(start.wrapping_add(i as u16)))
+            .collect()
+    }
+
+    pub fn dump_zero_page(&self) -> [u8; 256] {
+        std::array::from_fn(|i| self.bus.peek_u8(i as u16))
+    }
+
+    pub fn dump_stack(&self) -> [u8; 256] {
+        std::array::from_fn(|i| self.bus.peek_u8(0x0100 + i as u16))
+    }
+
     pub fn reset(&mut self) {
         self.reg_a = 0;
         self.reg_x = 0;
@@ -82,15 +223,102 @@ impl CPU {
     {
         while (self.status & StatusFlag::Break as u8) == 0 {
             callback(self);
+            self.step();
+        }
+    }
 
-            if self.bus.poll_nmi_status() {
-                self.interrupt_nmi();
-            }
+    // Calls `callback` after every CPU cycle, interleaved with the bus
+    // accesses of the running instruction. The bus has already clocked the
+    // PPU and APU for the cycle, so the callback must not clock them again
+    pub fn run_with_cycle_callback<F>(&mut self, callback: F)
+    where
+        F: FnMut(&mut Bus) + 'static,
+    {
+        self.cycle_hook = Some(Box::new(callback));
+        while (self.status & StatusFlag::Break as u8) == 0 {
+            self.step();
+        }
+        self.cycle_hook = None;
+    }
+
+    // Like `step`, but surfaces errors raised by the strict debug modes
+    pub fn try_step(&mut self) -> Result<u32, CpuError> {
+        let cycles = self.step();
+        match self.error.take() {
+            Some(error) => Err(error),
+            None => Ok(cycles),
+        }
+    }
+
+    // Steps `count` instructions and returns the cycles they took in total
+    pub fn step_n(&mut self, count: usize) -> u64 {
+        (0..count).map(|_| self.step() as u64).sum()
+    }
 
-            let opcode: OP = self.mem_read_pc_u8().into();
-            opcode.execute(self);
+    // Services a pending NMI, then executes one instruction and returns the
+    // cycles spent. While a DMA halts the CPU, a step only runs out the stall
+    pub fn step(&mut self) -> u32 {
+        self.error = None; // Only errors from this step reach `try_step`
+        if self.bus.dma_in_progress() {
+            let stall = self.bus.dma_stall();
+            self.tick_cycles(stall);
+            return stall;
+        }
+
+        let mut cycles = 0;
+        if self.bus.poll_nmi_status() {
+            cycles += self.interrupt_nmi();
+        }
 
-            self.bus.tick(opcode.cycles as u32);
+        if let Some(coverage) = self.coverage.as_mut()
+            && self.pc >= 0x8000
+        {
+            coverage[(self.pc - 0x8000) as usize] = true;
+        }
+
+        self.ticked_cycles = 0;
+        let opcode: OP = self.mem_read_pc_u8().into();
+        self.extra_cycles = 0;
+        opcode.execute(self);
+
+        let op_cycles = opcode.cycles as u32 + self.extra_cycles as u32;
+        self.tick_cycles(op_cycles.saturating_sub(self.ticked_cycles as u32));
+        self.bus.start_oam_dma();
+        cycles + op_cycles
+    }
+
+    // Lets the bus catch up to `cycles` into the instruction for
+    // timing-sensitive sequences
+    fn tick_within_instruction(&mut self, cycles: u8) {
+        let remaining = cycles.saturating_sub(self.ticked_cycles);
+        self.tick_cycles(remaining as u32);
+        self.ticked_cycles += remaining;
+    }
+
+    // With a cycle hook installed every bus access takes up its own cycle
+    fn tick_access(&mut self, accesses: u8) {
+        if self.cycle_hook.is_some() {
+            self.tick_cycles(accesses as u32);
+            self.ticked_cycles += accesses;
+        }
+    }
+
+    fn tick_cycles(&mut self, cycles: u32) {
+        match self.cycle_hook.as_mut() {
+            Some(hook) => {
+                for _ in 0..cycles {
+                    self.bus.tick(1);
+                    hook(&mut self.bus);
+                }
+            }
+            None => self.bus.tick(cycles),
+        }
+    }
+
+    // Charges the +1 cycle read instructions pay when indexing crossed a page
+    fn add_page_cross_cycle(&mut self) {
+        if self.page_crossed {
+            self.extra_cycles += 1;
         }
     }
 
@@ -107,6 +335,7 @@ impl CPU {
     }
 
     fn get_address(&mut self, addressing_mode: &AddressingMode) -> u16 {
+        self.page_crossed = false;
         match addressing_mode {
             AddressingMode::Immediate => {
                 self.pc += 1;
@@ -116,8 +345,14 @@ impl CPU {
             AddressingMode::ZeroPage_X => self.mem_read_pc_u8().wrapping_add(self.reg_x) as u16,
             AddressingMode::ZeroPage_Y => self.mem_read_pc_u8().wrapping_add(self.reg_y) as u16,
             AddressingMode::Absolute => self.mem_read_pc_u16(),
-            AddressingMode::Absolute_X => self.mem_read_pc_u16().wrapping_add(self.reg_x as u16),
-            AddressingMode::Absolute_Y => self.mem_read_pc_u16().wrapping_add(self.reg_y as u16),
+            AddressingMode::Absolute_X => {
+                let base = self.mem_read_pc_u16();
+                self.index_address(base, self.reg_x)
+            }
+            AddressingMode::Absolute_Y => {
+                let base = self.mem_read_pc_u16();
+                self.index_address(base, self.reg_y)
+            }
             AddressingMode::Indirect => {
                 let ptr = self.mem_read_pc_u16();
                 let lo = self.mem_read_u8(ptr) as u16;
@@ -137,13 +372,41 @@ impl CPU {
                 let hi = self.mem_read_u8((ptr).wrapping_add(1) as u16) as u16;
                 let deref_base = hi << 8 | lo;
 
-                deref_base.wrapping_add(self.reg_y as u16)
+                self.index_address(deref_base, self.reg_y)
             }
             AddressingMode::Accumulator => panic!("mode {:?} is not an address", addressing_mode),
             _ => panic!("mode {:?} is not supported", addressing_mode),
         }
     }
 
+    fn index_address(&mut self, base: u16, index: u8) -> u16 {
+        let addr = base.wrapping_add(index as u16);
+        self.page_crossed = base & 0xFF00 != addr & 0xFF00;
+        addr
+    }
+
+    // Read-modify-write instructions always spend the indexing fixup cycle,
+    // reading from the address before the high byte carry is applied
+    fn get_rmw_address(&mut self, mode: &AddressingMode) -> u16 {
+        let addr = self.get_address(mode);
+        if mode.can_cross_page() {
+            let uncarried = if self.page_crossed {
+                addr.wrapping_sub(0x100)
+            } else {
+                addr
+            };
+            self.mem_read_u8(uncarried);
+        }
+        addr
+    }
+
+    // The 6502 writes the unmodified value back before the result, which
+    // registers with write side effects (e.g. $2007) see as two writes
+    fn rmw_write(&mut self, addr: u16, old: u8, new: u8) {
+        self.mem_write_u8(addr, old);
+        self.mem_write_u8(addr, new);
+    }
+
     fn try_get_address(&mut self, mode: &AddressingMode) -> Option<u16> {
         match mode {
             AddressingMode::Relative
@@ -164,11 +427,17 @@ impl CPU {
     }
 
     fn stack_push_value_u8(&mut self, value: u8) {
+        if self.strict_stack && self.stack == 0x00 {
+            self.error = Some(CpuError::StackOverflow);
+        }
         self.mem_write_u8(self.get_stack_address(), value);
         self.stack = self.stack.wrapping_sub(1);
     }
 
     fn stack_pull_value_u8(&mut self) -> u8 {
+        if self.strict_stack && self.stack == 0xFF {
+            self.error = Some(CpuError::StackUnderflow);
+        }
         self.stack = self.stack.wrapping_add(1);
         self.mem_read_u8(self.get_stack_address())
     }
@@ -200,6 +469,11 @@ impl CPU {
         let offset = self.mem_read_pc_u8() as i8;
         if condition {
             let jump_addr = self.pc.wrapping_add(offset as u16);
+            // +1 for a taken branch, +1 more when it lands on another page
+            self.extra_cycles += 1;
+            if self.pc & 0xFF00 != jump_addr & 0xFF00 {
+                self.extra_cycles += 1;
+            }
             self.pc = jump_addr;
         }
     }
@@ -231,104 +505,7 @@ impl CPU {
         let ins_str = format!(
             "{: >4} {}",
             op.name,
-            match op.mode {
-                AddressingMode::Immediate => format!("#${:02X}", instructions[1]),
-                AddressingMode::ZeroPage => format!(
-                    "${:02X} = {:02X}",
-                    instructions[1],
-                    self.mem_read_u8(instructions[1] as u16)
-                ),
-                AddressingMode::ZeroPage_X => {
-                    let addr = instructions[1].wrapping_add(self.reg_x);
-                    format!(
-                        "${:02X},X @ {:02X} = {:02X}",
-                        instructions[1],
-                        addr,
-                        self.mem_read_u8(addr as u16)
-                    )
-                }
-                AddressingMode::ZeroPage_Y => {
-                    let addr = instructions[1].wrapping_add(self.reg_y);
-                    format!(
-                        "${:02X},Y @ {:02X} = {:02X}",
-                        instructions[1],
-                        addr,
-                        self.mem_read_u8(addr as u16)
-                    )
-                }
-                AddressingMode::Absolute => {
-                    if op.name == "JMP" || op.name == "JSR" {
-                        format!(
-                            "${:04X}",
-                            u16::from_le_bytes([instructions[1], instructions[2]])
-                        )
-                    } else {
-                        let addr = u16::from_le_bytes([instructions[1], instructions[2]]);
-                        format!("${:04X} = {:02X}", addr, self.mem_read_u8(addr))
-                    }
-                }
-                AddressingMode::Absolute_X => {
-                    let addr = u16::from_le_bytes([instructions[1], instructions[2]]);
-                    let addr_final = addr.wrapping_add(self.reg_x as u16);
-                    format!(
-                        "${:04X},X @ {:04X} = {:02X}",
-                        addr,
-                        addr_final,
-                        self.mem_read_u8(addr_final)
-                    )
-                }
-                AddressingMode::Absolute_Y => {
-                    let addr = u16::from_le_bytes([instructions[1], instructions[2]]);
-                    let addr_final = addr.wrapping_add(self.reg_y as u16);
-                    format!(
-                        "${:04X},Y @ {:04X} = {:02X}",
-                        addr,
-                        addr_final,
-                        self.mem_read_u8(addr_final)
-                    )
-                }
-                AddressingMode::Indirect => {
-                    let ptr = u16::from_le_bytes([instructions[1], instructions[2]]);
-                    let lo = self.mem_read_u8(ptr) as u16;
-                    let hi = self.mem_read_u8(ptr & 0xFF00 | ((ptr as u8).wrapping_add(1) as u16))
-                        as u16; // Replicate the page boundary bug in the original 6502
-                    let ptr_2 = hi << 8 | lo;
-                    format!("(${:04X}) = {:04X}", ptr, ptr_2,)
-                }
-                AddressingMode::Indirect_X => {
-                    let ptr = instructions[1].wrapping_add(self.reg_x);
-                    let lo = self.mem_read_u8(ptr as u16) as u16;
-                    let hi = self.mem_read_u8(ptr.wrapping_add(1) as u16) as u16;
-                    let ptr_final = hi << 8 | lo;
-                    format!(
-                        "(${:02X},X) @ {:02X} = {:04X} = {:02X}",
-                        instructions[1],
-                        ptr,
-                        ptr_final,
-                        self.mem_read_u8(ptr_final),
-                    )
-                }
-                AddressingMode::Indirect_Y => {
-                    let lo = self.mem_read_u8(instructions[1] as u16) as u16;
-                    let hi = self.mem_read_u8(instructions[1].wrapping_add(1) as u16) as u16;
-                    let ptr = hi << 8 | lo;
-                    let ptr_final = ptr.wrapping_add(self.reg_y as u16);
-                    format!(
-                        "(${:02X}),Y = {:04X} @ {:04X} = {:02X}",
-                        instructions[1],
-                        ptr,
-                        ptr_final,
-                        self.mem_read_u8(ptr_final),
-                    )
-                }
-                AddressingMode::Relative => {
-                    let offset = instructions[1] as i8;
-                    let jump_addr = self.pc.wrapping_add(offset as u16 + 2);
-                    format!("${:04X}", jump_addr)
-                }
-                AddressingMode::Accumulator => "A".to_string(),
-                _ => "".to_string(),
-            }
+            self.format_operand(&op, &instructions)
         );
 
         let reg_str = format!(
@@ -339,7 +516,130 @@ impl CPU {
         format!("{:5} {:8} {:32} {}", pc_str, code_str, ins_str, reg_str)
     }
 
-    fn interrupt_nmi(&mut self) {
+    // Formats the operand of `op` as it appears in nestest logs. `bytes` holds
+    // the whole instruction, opcode included, and memory is only peeked.
+    pub fn format_operand(&self, op: &OP, bytes: &[u8]) -> String {
+        match op.mode {
+            AddressingMode::Immediate => format!("#${:02X}", bytes[1]),
+            AddressingMode::ZeroPage => format!(
+                "${:02X} = {:02X}",
+                bytes[1],
+                self.bus.peek_u8(bytes[1] as u16)
+            ),
+            AddressingMode::ZeroPage_X => {
+                let addr = bytes[1].wrapping_add(self.reg_x);
+                format!(
+                    "${:02X},X @ {:02X} = {:02X}",
+                    bytes[1],
+                    addr,
+                    self.bus.peek_u8(addr as u16)
+                )
+            }
+            AddressingMode::ZeroPage_Y => {
+                let addr = bytes[1].wrapping_add(self.reg_y);
+                format!(
+                    "${:02X},Y @ {:02X} = {:02X}",
+                    bytes[1],
+                    addr,
+                    self.bus.peek_u8(addr as u16)
+                )
+            }
+            AddressingMode::Absolute => {
+                if op.name == "JMP" || op.name == "JSR" {
+                    format!("${:04X}", u16::from_le_bytes([bytes[1], bytes[2]]))
+                } else {
+                    let addr = u16::from_le_bytes([bytes[1], bytes[2]]);
+                    format!("${:04X} = {:02X}", addr, self.bus.peek_u8(addr))
+                }
+            }
+            AddressingMode::Absolute_X => {
+                let addr = u16::from_le_bytes([bytes[1], bytes[2]]);
+                let addr_final = addr.wrapping_add(self.reg_x as u16);
+                format!(
+                    "${:04X},X @ {:04X} = {:02X}",
+                    addr,
+                    addr_final,
+                    self.bus.peek_u8(addr_final)
+                )
+            }
+            AddressingMode::Absolute_Y => {
+                let addr = u16::from_le_bytes([bytes[1], bytes[2]]);
+                let addr_final = addr.wrapping_add(self.reg_y as u16);
+                format!(
+                    "${:04X},Y @ {:04X} = {:02X}",
+                    addr,
+                    addr_final,
+                    self.bus.peek_u8(addr_final)
+                )
+            }
+            AddressingMode::Indirect => {
+                let ptr = u16::from_le_bytes([bytes[1], bytes[2]]);
+                let lo = self.bus.peek_u8(ptr) as u16;
+                let hi = self
+                    .bus
+                    .peek_u8(ptr & 0xFF00 | ((ptr as u8).wrapping_add(1) as u16))
+                    as u16; // Replicate the page boundary bug in the original 6502
+                let ptr_2 = hi << 8 | lo;
+                format!("(${:04X}) = {:04X}", ptr, ptr_2,)
+            }
+            AddressingMode::Indirect_X => {
+                let ptr = bytes[1].wrapping_add(self.reg_x);
+                let lo = self.bus.peek_u8(ptr as u16) as u16;
+                let hi = self.bus.peek_u8(ptr.wrapping_add(1) as u16) as u16;
+                let ptr_final = hi << 8 | lo;
+                format!(
+                    "(${:02X},X) @ {:02X} = {:04X} = {:02X}",
+                    bytes[1],
+                    ptr,
+                    ptr_final,
+                    self.bus.peek_u8(ptr_final),
+                )
+            }
+            AddressingMode::Indirect_Y => {
+                let lo = self.bus.peek_u8(bytes[1] as u16) as u16;
+                let hi = self.bus.peek_u8(bytes[1].wrapping_add(1) as u16) as u16;
+                let ptr = hi << 8 | lo;
+                let ptr_final = ptr.wrapping_add(self.reg_y as u16);
+                format!(
+                    "(${:02X}),Y = {:04X} @ {:04X} = {:02X}",
+                    bytes[1],
+                    ptr,
+                    ptr_final,
+                    self.bus.peek_u8(ptr_final),
+                )
+            }
+            AddressingMode::Relative => {
+                let offset = bytes[1] as i8;
+                let jump_addr = self.pc.wrapping_add(2).wrapping_add(offset as u16);
+                format!("${:04X}", jump_addr)
+            }
+            AddressingMode::Accumulator => "A".to_string(),
+            _ => "".to_string(),
+        }
+    }
+
+    fn interrupt_brk(&mut self) {
+        // The byte after BRK is padding and skipped on return
+        self.stack_push_value_u16(self.pc.wrapping_add(1));
+        let mut flag = self.status;
+        flag = set_bit(flag, StatusFlag::Break as u8, true);
+        flag = set_bit(flag, StatusFlag::Break2 as u8, true);
+        self.stack_push_value_u8(flag);
+        self.status = set_bit(self.status, StatusFlag::InterruptDisable as u8, true);
+
+        // The vector is picked after the pushes (cycle 5); an NMI asserted
+        // by then hijacks the BRK and it jumps through the NMI vector instead
+        self.tick_within_instruction(4);
+        let vector = if self.bus.poll_nmi_status() {
+            0xFFFA
+        } else {
+            0xFFFE
+        };
+        self.pc = self.mem_read_u16(vector);
+    }
+
+    fn interrupt_nmi(&mut self) -> u32 {
+        self.ticked_cycles = 0;
         self.stack_push_value_u16(self.pc);
         let mut flag = self.status;
         flag = set_bit(flag, StatusFlag::Break as u8, false);
@@ -348,24 +648,31 @@ impl CPU {
 
         self.status = set_bit(self.status, StatusFlag::InterruptDisable as u8, true);
         self.pc = self.mem_read_u16(0xFFFA);
-        self.bus.tick(2);
+        self.tick_within_instruction(2);
+        self.ticked_cycles as u32
     }
 }
 
 impl Memory for CPU {
     fn mem_read_u8(&mut self, addr: u16) -> u8 {
-        self.bus.mem_read_u8(addr)
+        let value = self.bus.mem_read_u8(addr);
+        self.tick_access(1);
+        value
     }
 
     fn mem_write_u8(&mut self, addr: u16, data: u8) {
-        self.bus.mem_write_u8(addr, data)
+        self.bus.mem_write_u8(addr, data);
+        self.tick_access(1);
     }
     fn mem_read_u16(&mut self, pos: u16) -> u16 {
-        self.bus.mem_read_u16(pos)
+        let value = self.bus.mem_read_u16(pos);
+        self.tick_access(2);
+        value
     }
 
     fn mem_write_u16(&mut self, pos: u16, data: u16) {
-        self.bus.mem_write_u16(pos, data)
+        self.bus.mem_write_u16(pos, data);
+        self.tick_access(2);
     }
 }
 
@@ -386,6 +693,11 @@ pub enum StatusFlag {
 #[cfg(test)]
 mod memory_test {
     use super::*;
+    use crate::mem::rom::Mirroring;
+    use std::{
+        cell::{Cell, RefCell},
+        rc::Rc,
+    };
 
     // Memory tests
     #[test]
@@ -417,6 +729,202 @@ mod memory_test {
         assert_eq!(value, data);
     }
 
+    #[test]
+    fn test_ppu_access_through_cpu() {
+        let mut cpu = CPU::new();
+        assert!(cpu.ppu().is_none());
+
+        let rom_data = Rom::create_rom_data(1, 1, 0x00, 0b0000_0001, false);
+        cpu.insert_rom(Rom::new(&rom_data).unwrap());
+
+        assert_eq!(cpu.ppu().unwrap().mirroring, Mirroring::Vertical);
+        cpu.ppu_mut().unwrap().mirroring = Mirroring::Horizontal;
+        assert_eq!(cpu.ppu().unwrap().mirroring, Mirroring::Horizontal);
+    }
+
+    #[test]
+    fn test_dump_zero_page() {
+        let mut cpu = CPU::new();
+        cpu.mem_write_u8(0x0000, 0x11);
+        cpu.mem_write_u8(0x00FF, 0x22);
+        cpu.mem_write_u8(0x0100, 0x33); // Outside the zero page
+
+        let zero_page = cpu.dump_zero_page();
+        assert_eq!(zero_page[0x00], 0x11);
+        assert_eq!(zero_page[0xFF], 0x22);
+    }
+
+    #[test]
+    fn test_dump_stack() {
+        let mut cpu = CPU::new();
+        // LDA #$42, PHA, LDA #$43, PHA, BRK
+        cpu.load_and_run(vec![0xa9, 0x42, 0x48, 0xa9, 0x43, 0x48, 0x00]);
+
+        let stack = cpu.dump_stack();
+        assert_eq!(cpu.stack, 0xFD);
+        assert_eq!(stack[cpu.stack as usize + 1], 0x43); // Top of the stack
+        assert_eq!(stack[cpu.stack as usize + 2], 0x42);
+    }
+
+    #[test]
+    fn test_sta_to_prg_rom_is_discarded() {
+        let mut cpu = CPU::new();
+        // LDA #$42, STA $8000, BRK
+        cpu.load_and_run(vec![0xa9, 0x42, 0x8d, 0x00, 0x80, 0x00]);
+
+        assert_eq!(cpu.mem_read_u8(0x8000), 0x00);
+    }
+
+    #[test]
+    fn test_load_at_seeds_prg_rom() {
+        let mut cpu = CPU::new();
+        cpu.insert_rom(Rom::from_pc(0x8000));
+        cpu.load_at(vec![0xa9, 0x42, 0x00], 0x8000);
+
+        assert_eq!(cpu.mem_read_u8(0x8000), 0xa9);
+        assert_eq!(cpu.mem_read_u8(0x8001), 0x42);
+        assert_eq!(cpu.pc, 0x8000);
+    }
+
+    // Strict stack tests
+    #[test]
+    fn test_strict_stack_overflow() {
+        let mut cpu = CPU::new();
+        cpu.load(vec![0x48; 0x200]); // PHA
+        cpu.reg_a = 0x48; // The stack page overlaps the program, so push PHA opcodes
+        cpu.strict_stack = true;
+
+        for _ in 0..255 {
+            assert!(cpu.try_step().is_ok());
+        }
+        assert_eq!(cpu.stack, 0x00);
+        assert_eq!(cpu.try_step(), Err(CpuError::StackOverflow));
+    }
+
+    #[test]
+    fn test_strict_stack_underflow() {
+        let mut cpu = CPU::new();
+        cpu.load(vec![0x68]); // PLA
+        cpu.strict_stack = true;
+
+        assert_eq!(cpu.try_step(), Err(CpuError::StackUnderflow));
+    }
+
+    #[test]
+    fn test_strict_stack_error_does_not_outlive_step() {
+        let mut cpu = CPU::new();
+        cpu.load(vec![0x68, 0xEA]); // PLA; NOP
+        cpu.strict_stack = true;
+
+        cpu.step(); // The underflow is not surfaced through a plain step
+        assert_eq!(cpu.try_step(), Ok(2));
+    }
+
+    #[test]
+    fn test_stack_u16_wraps_per_byte() {
+        let mut cpu = CPU::new();
+        cpu.stack = 0x00;
+
+        cpu.stack_push_value_u16(0xABCD);
+        assert_eq!(cpu.mem_read_u8(0x0100), 0xAB); // High byte first
+        assert_eq!(cpu.mem_read_u8(0x01FF), 0xCD); // Low byte after the wrap
+        assert_eq!(cpu.stack, 0xFE);
+
+        assert_eq!(cpu.stack_pull_value_u16(), 0xABCD);
+        assert_eq!(cpu.stack, 0x00);
+    }
+
+    #[test]
+    fn test_stack_wraps_without_strict_mode() {
+        let mut cpu = CPU::new();
+        cpu.load(vec![0x48; 0x200]); // PHA
+        cpu.reg_a = 0x48; // The stack page overlaps the program, so push PHA opcodes
+
+        for _ in 0..300 {
+            assert!(cpu.try_step().is_ok());
+        }
+        assert_eq!(cpu.stack, 0xFFu8.wrapping_sub(300u16 as u8));
+    }
+
+    // Operand formatting tests
+    #[test]
+    fn test_format_operand_all_modes() {
+        let mut cpu = CPU::new();
+        cpu.reg_x = 0x05;
+        cpu.reg_y = 0x07;
+        cpu.pc = 0x0600;
+        cpu.mem_write_u8(0x0010, 0x55);
+        cpu.mem_write_u8(0x0015, 0x66);
+        cpu.mem_write_u8(0x0017, 0x77);
+        cpu.mem_write_u8(0x0200, 0x88);
+        cpu.mem_write_u8(0x0205, 0x99);
+        cpu.mem_write_u8(0x0207, 0xAA);
+        cpu.mem_write_u8(0x02FF, 0x34);
+        cpu.mem_write_u16(0x0025, 0x0300);
+        cpu.mem_write_u16(0x0040, 0x0300);
+        cpu.mem_write_u8(0x0300, 0xBB);
+        cpu.mem_write_u8(0x0307, 0xCC);
+
+        let cases: [(&[u8], &str); 14] = [
+            (&[0xA9, 0x42], "#$42"),
+            (&[0xA5, 0x10], "$10 = 55"),
+            (&[0xB5, 0x10], "$10,X @ 15 = 66"),
+            (&[0xB6, 0x10], "$10,Y @ 17 = 77"),
+            (&[0xAD, 0x00, 0x02], "$0200 = 88"),
+            (&[0x4C, 0x34, 0x12], "$1234"),
+            (&[0xBD, 0x00, 0x02], "$0200,X @ 0205 = 99"),
+            (&[0xB9, 0x00, 0x02], "$0200,Y @ 0207 = AA"),
+            (&[0x6C, 0xFF, 0x02], "($02FF) = 8834"), // High byte wraps to 0x0200
+            (&[0xA1, 0x20], "($20,X) @ 25 = 0300 = BB"),
+            (&[0xB1, 0x40], "($40),Y = 0300 @ 0307 = CC"),
+            (&[0xD0, 0xFE], "$0600"),
+            (&[0x0A], "A"),
+            (&[0xEA], ""),
+        ];
+
+        for (bytes, expected) in cases {
+            let op: OP = bytes[0].into();
+            assert_eq!(cpu.format_operand(&op, bytes), expected);
+        }
+    }
+
+    #[test]
+    fn test_format_operand_peeks_registers() {
+        let mut cpu = CPU::new();
+        let rom_data = Rom::create_rom_data(1, 1, 0x00, 0x00, false);
+        cpu.insert_rom(Rom::new(&rom_data).unwrap());
+        cpu.ppu_mut().unwrap().tick(241 * 341);
+
+        // I/O registers are not peekable, so they print as 00
+        let op: OP = 0xAD.into();
+        assert_eq!(cpu.format_operand(&op, &[0xAD, 0x02, 0x20]), "$2002 = 00");
+
+        // Formatting did not read PPUSTATUS and clear VBlank
+        assert_eq!(cpu.ppu_mut().unwrap().read_status() & 0x80, 0x80);
+    }
+
+    #[test]
+    fn test_with_bus() {
+        let bus = Bus::from_rom(Rom::from_pc(0x8123));
+        let mut cpu = CPU::with_bus(bus);
+        cpu.reset();
+
+        assert_eq!(cpu.pc(), 0x8123);
+        assert!(cpu.ppu().is_some());
+    }
+
+    #[test]
+    fn test_print_state_uses_format_operand() {
+        let mut cpu = CPU::new();
+        cpu.load(vec![0xa5, 0x10, 0x00]); // LDA $10
+        cpu.mem_write_u8(0x0010, 0x42);
+
+        let op: OP = 0xa5.into();
+        let operand = cpu.format_operand(&op, &[0xa5, 0x10]);
+        assert_eq!(operand, "$10 = 42");
+        assert!(cpu.print_state().contains(&format!(" LDA {}", operand)));
+    }
+
     // General Instruction tests
     #[test]
     fn test_5_ops_working_together() {
@@ -513,4 +1021,158 @@ mod memory_test {
         // Should have called callback for each instruction (including final BRK)
         assert!(tick_count >= 3);
     }
+
+    #[test]
+    fn test_run_with_cycle_callback_counts_cycles() {
+        let mut cpu = CPU::new();
+        // LDA #$01 (2), LDX #$02 (2), INX (2), STA $10 (3), BRK (7)
+        cpu.load(vec![0xa9, 0x01, 0xa2, 0x02, 0xe8, 0x85, 0x10, 0x00]);
+
+        let cycle_count = Rc::new(Cell::new(0));
+        let counter = Rc::clone(&cycle_count);
+        cpu.run_with_cycle_callback(move |_bus| counter.set(counter.get() + 1));
+
+        assert_eq!(cycle_count.get(), 16);
+        assert_eq!(cpu.bus.cpu_cycles(), 16);
+    }
+
+    #[test]
+    fn test_run_with_cycle_callback_interleaves_accesses() {
+        let mut cpu = CPU::new();
+        // LDA #$42 (2), STA $10 (3), BRK
+        cpu.load(vec![0xa9, 0x42, 0x85, 0x10, 0x00]);
+
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let log = Rc::clone(&seen);
+        cpu.run_with_cycle_callback(move |bus| {
+            log.borrow_mut().push((bus.cpu_cycles(), bus.peek_u8(0x10)));
+        });
+
+        // One call per cycle, and the STA write only shows on its third cycle
+        let seen = seen.borrow();
+        assert_eq!(seen[0].0, 1);
+        assert_eq!(seen[1].0, 2);
+        assert_eq!(seen[2..5], [(3, 0x00), (4, 0x00), (5, 0x42)]);
+    }
+
+    #[test]
+    fn test_registers_snapshot() {
+        let mut cpu = CPU::new();
+        cpu.load(vec![0xa2, 0x80, 0x00]); // LDX #$80
+
+        let before = cpu.registers();
+        cpu.step();
+        let after = cpu.registers();
+
+        assert_ne!(before, after);
+        assert_eq!(
+            after,
+            Registers {
+                x: 0x80,
+                pc: before.pc + 2,
+                status: before.status | StatusFlag::Negative as u8,
+                ..before
+            }
+        );
+    }
+
+    #[test]
+    fn test_last_opcode() {
+        let mut cpu = CPU::new();
+        cpu.load(vec![0xa9, 0x05, 0xe8, 0xaa]); // LDA #$05, INX, TAX
+
+        for _ in 0..3 {
+            cpu.step();
+        }
+
+        assert_eq!(cpu.last_opcode(), 0xaa);
+        assert_eq!(cpu.last_opcode_pc(), 0x0003);
+    }
+
+    #[test]
+    fn test_memory_dump() {
+        let mut cpu = CPU::new();
+        cpu.load(vec![0x00]);
+        cpu.mem_write_u8(0x07FE, 0x11);
+        cpu.mem_write_u8(0x07FF, 0x22);
+        cpu.mem_write_u8(0x2006, 0x20); // Leaves the PPU address latch half written
+
+        assert_eq!(cpu.memory_dump(0x07FE, 2), vec![0x11, 0x22]);
+
+        // $1FFE/$1FFF mirror $07FE/$07FF; PPU registers peek as 0
+        assert_eq!(cpu.memory_dump(0x1FFE, 4), vec![0x11, 0x22, 0x00, 0x00]);
+
+        // Peeking $2002 did not reset the write toggle: this completes $2000
+        cpu.mem_write_u8(0x2006, 0x00);
+        assert_eq!(cpu.ppu().unwrap().vram_addr(), 0x2000);
+    }
+
+    #[test]
+    fn test_patched_opcode_fetch() {
+        let mut cpu = CPU::new();
+        cpu.insert_rom(Rom::from_pc(0x8000));
+        cpu.load_at(vec![0xE8, 0xE8, 0x00], 0x8000); // INX; INX; BRK
+        cpu.bus.patch(0x8000, 0xC8); // INY
+
+        cpu.run();
+        assert_eq!(cpu.reg_x, 1);
+        assert_eq!(cpu.reg_y, 1);
+        assert_eq!(cpu.bus.peek_u8(0x8000), 0xE8);
+    }
+
+    #[test]
+    fn test_step_n() {
+        let mut cpu = CPU::new();
+        cpu.insert_rom(Rom::from_pc(0x8000));
+        // LDA #$01; STA $10; INC $10; LDX $10; NOP
+        cpu.load_at(
+            vec![0xA9, 0x01, 0x85, 0x10, 0xE6, 0x10, 0xA6, 0x10, 0xEA],
+            0x8000,
+        );
+
+        assert_eq!(cpu.step_n(5), 2 + 3 + 5 + 3 + 2);
+        assert_eq!(cpu.pc, 0x8009);
+        assert_eq!(cpu.reg_x, 2);
+    }
+
+    #[test]
+    fn test_set_pc() {
+        let mut cpu = CPU::new();
+        cpu.insert_rom(Rom::from_pc(0x8000));
+        // INX at $8000, INY at $8001
+        cpu.load_at(vec![0xE8, 0xC8], 0x8000);
+
+        cpu.set_pc(0x8001);
+        cpu.step();
+
+        assert_eq!(cpu.reg_x, 0);
+        assert_eq!(cpu.reg_y, 1);
+        assert_eq!(cpu.pc(), 0x8002);
+    }
+
+    #[test]
+    fn test_oam_dma_halts_cpu() {
+        let mut cpu = CPU::new();
+        cpu.insert_rom(Rom::from_pc(0x8000));
+        // LDA #$02; STA $4014; INX
+        cpu.load_at(vec![0xA9, 0x02, 0x8D, 0x14, 0x40, 0xE8], 0x8000);
+        for i in 0..=255u8 {
+            cpu.mem_write_u8(0x0200 + i as u16, i);
+        }
+
+        cpu.step_n(2);
+        assert!(cpu.bus.dma_in_progress());
+        assert_eq!(cpu.ppu().unwrap().oam_data[0x80], 0x80);
+
+        cpu.bus.tick(100); // Part of the stall window
+        assert!(cpu.bus.dma_in_progress());
+
+        // Started after 6 cycles (even), so 513 cycles in total
+        assert_eq!(cpu.step(), 513 - 100);
+        assert!(!cpu.bus.dma_in_progress());
+        assert_eq!(cpu.reg_x, 0);
+
+        cpu.step();
+        assert_eq!(cpu.reg_x, 1);
+    }
 }
@@ -2,13 +2,21 @@ use crate::cpu::{CPU, StatusFlag, opcode::AddressingMode};
 
 pub(crate) fn adc(cpu: &mut CPU, mode: AddressingMode) {
     let (_, value) = cpu.get_address_and_value(&mode);
-    cpu_addition_with_carry(cpu, value);
+    if cpu.bcd_enabled && cpu.get_flag(StatusFlag::Decimal) {
+        cpu_decimal_addition_with_carry(cpu, value);
+    } else {
+        cpu_addition_with_carry(cpu, value);
+    }
 }
 
 pub(crate) fn sbc(cpu: &mut CPU, mode: AddressingMode) {
     let (_, value) = cpu.get_address_and_value(&mode);
-    let complement_value = value ^ 0xFF;
-    cpu_addition_with_carry(cpu, complement_value);
+    if cpu.bcd_enabled && cpu.get_flag(StatusFlag::Decimal) {
+        cpu_decimal_subtraction_with_borrow(cpu, value);
+    } else {
+        let complement_value = value ^ 0xFF;
+        cpu_addition_with_carry(cpu, complement_value);
+    }
 }
 
 pub(crate) fn cmp(cpu: &mut CPU, mode: AddressingMode) {
@@ -66,6 +74,55 @@ pub(crate) fn cpu_addition_with_carry(cpu: &mut CPU, value: u8) {
     cpu.update_zero_and_negative_flags(cpu.reg_a);
 }
 
+// NMOS 6502 decimal ADC: Z comes from the plain binary sum, N and V from the
+// sum after the low nibble correction but before the high nibble one
+pub(crate) fn cpu_decimal_addition_with_carry(cpu: &mut CPU, value: u8) {
+    let carry_in = cpu.get_flag(StatusFlag::Carry) as u16;
+    let a = cpu.reg_a as u16;
+    let b = value as u16;
+
+    let binary_result = a.wrapping_add(b).wrapping_add(carry_in) as u8;
+
+    let mut low = (a & 0x0F) + (b & 0x0F) + carry_in;
+    if low >= 0x0A {
+        low = ((low + 0x06) & 0x0F) + 0x10;
+    }
+    let mut result = (a & 0xF0) + (b & 0xF0) + low;
+
+    let intermediate = result as u8;
+    let overflow_flag = (value ^ intermediate) & (cpu.reg_a ^ intermediate) & 0b1000_0000 != 0;
+
+    if result >= 0xA0 {
+        result += 0x60;
+    }
+
+    cpu.reg_a = result as u8;
+
+    cpu.set_flag(StatusFlag::Carry, result >= 0x100);
+    cpu.set_flag(StatusFlag::Overflow, overflow_flag);
+    cpu.set_flag(StatusFlag::Zero, binary_result == 0);
+    cpu.set_flag(StatusFlag::Negative, intermediate & 0b1000_0000 != 0);
+}
+
+// NMOS 6502 decimal SBC: every flag matches binary SBC, only A is corrected
+pub(crate) fn cpu_decimal_subtraction_with_borrow(cpu: &mut CPU, value: u8) {
+    let borrow = !cpu.get_flag(StatusFlag::Carry) as i16;
+    let a = cpu.reg_a as i16;
+    let b = value as i16;
+
+    let mut low = (a & 0x0F) - (b & 0x0F) - borrow;
+    if low < 0 {
+        low = ((low - 0x06) & 0x0F) - 0x10;
+    }
+    let mut result = (a & 0xF0) - (b & 0xF0) + low;
+    if result < 0 {
+        result -= 0x60;
+    }
+
+    cpu_addition_with_carry(cpu, value ^ 0xFF);
+    cpu.reg_a = result as u8;
+}
+
 #[cfg(test)]
 mod arithmetic_tests {
     use super::*;
@@ -186,6 +243,62 @@ mod arithmetic_tests {
             assert_eq!(cpu.status & StatusFlag::Negative as u8, 0);
             assert_eq!(cpu.status & StatusFlag::Overflow as u8, 0);
         }
+
+        #[test]
+        fn test_adc_decimal_nmos_flags() {
+            let mut cpu = crate::cpu::CPU::new();
+            cpu.bcd_enabled = true;
+            cpu.status |= StatusFlag::Decimal as u8;
+            cpu.reg_a = 0x99;
+            cpu.mem_write_u8(0x10, 0x01);
+            cpu.pc = 0x0600;
+            cpu.mem_write_u8(0x0600, 0x10);
+
+            adc(&mut cpu, AddressingMode::ZeroPage);
+
+            // 99 + 01 = 100 in BCD, but Z comes from the binary sum 0x9A and
+            // N from the half-corrected sum 0xA0
+            assert_eq!(cpu.reg_a, 0x00);
+            assert_ne!(cpu.status & StatusFlag::Carry as u8, 0);
+            assert_eq!(cpu.status & StatusFlag::Zero as u8, 0);
+            assert_ne!(cpu.status & StatusFlag::Negative as u8, 0);
+            assert_eq!(cpu.status & StatusFlag::Overflow as u8, 0);
+        }
+
+        #[test]
+        fn test_adc_decimal_nmos_overflow() {
+            let mut cpu = crate::cpu::CPU::new();
+            cpu.bcd_enabled = true;
+            cpu.status |= StatusFlag::Decimal as u8 | StatusFlag::Carry as u8;
+            cpu.reg_a = 0x79;
+            cpu.mem_write_u8(0x10, 0x00);
+            cpu.pc = 0x0600;
+            cpu.mem_write_u8(0x0600, 0x10);
+
+            adc(&mut cpu, AddressingMode::ZeroPage);
+
+            // 79 + 00 + 1 = 80 in BCD, which reads as a signed overflow
+            assert_eq!(cpu.reg_a, 0x80);
+            assert_eq!(cpu.status & StatusFlag::Carry as u8, 0);
+            assert_eq!(cpu.status & StatusFlag::Zero as u8, 0);
+            assert_ne!(cpu.status & StatusFlag::Negative as u8, 0);
+            assert_ne!(cpu.status & StatusFlag::Overflow as u8, 0);
+        }
+
+        #[test]
+        fn test_adc_decimal_flag_ignored_on_2a03() {
+            let mut cpu = crate::cpu::CPU::new();
+            cpu.status |= StatusFlag::Decimal as u8;
+            cpu.reg_a = 0x99;
+            cpu.mem_write_u8(0x10, 0x01);
+            cpu.pc = 0x0600;
+            cpu.mem_write_u8(0x0600, 0x10);
+
+            adc(&mut cpu, AddressingMode::ZeroPage);
+
+            assert_eq!(cpu.reg_a, 0x9A); // Plain binary addition
+            assert_eq!(cpu.status & StatusFlag::Carry as u8, 0);
+        }
     }
 
     mod sbc_tests {
@@ -392,6 +505,69 @@ mod arithmetic_tests {
             assert_ne!(cpu.status & StatusFlag::Zero as u8, 0); // Zero flag should be set
             assert_eq!(cpu.status & StatusFlag::Negative as u8, 0);
         }
+
+        #[test]
+        fn test_sbc_decimal_nmos_flags() {
+            let mut cpu = CPU::new();
+            cpu.bcd_enabled = true;
+            cpu.status |= StatusFlag::Decimal as u8 | StatusFlag::Carry as u8;
+            cpu.reg_a = 0x00;
+            cpu.mem_write_u8(0x10, 0x01);
+            cpu.pc = 0x0600;
+            cpu.mem_write_u8(0x0600, 0x10);
+
+            sbc(&mut cpu, AddressingMode::ZeroPage);
+
+            // 00 - 01 = 99 with borrow in BCD, flags follow the binary 0xFF
+            assert_eq!(cpu.reg_a, 0x99);
+            assert_eq!(cpu.status & StatusFlag::Carry as u8, 0);
+            assert_eq!(cpu.status & StatusFlag::Zero as u8, 0);
+            assert_ne!(cpu.status & StatusFlag::Negative as u8, 0);
+            assert_eq!(cpu.status & StatusFlag::Overflow as u8, 0);
+        }
+
+        // Runs one SBC immediate with `opcode` and returns (A, status, PC delta, cycles)
+        fn run_sbc_immediate(
+            opcode: u8,
+            a: u8,
+            operand: u8,
+            status: u8,
+            bcd: bool,
+        ) -> (u8, u8, u16, u32) {
+            let mut cpu = CPU::new();
+            cpu.load(vec![opcode, operand, 0x00]);
+            cpu.bcd_enabled = bcd;
+            cpu.reg_a = a;
+            cpu.status = status;
+
+            let cycles = cpu.step();
+            (cpu.reg_a, cpu.status, cpu.pc, cycles)
+        }
+
+        #[test]
+        fn test_sbc_0xeb_matches_0xe9() {
+            let carry = StatusFlag::Carry as u8;
+            let decimal = StatusFlag::Decimal as u8;
+            let cases = [
+                (0x50, 0x30, carry, false),
+                (0x50, 0x30, 0x00, false),
+                (0x80, 0x01, carry, false), // Signed overflow
+                (0x00, 0x01, carry, false), // Borrow
+                (0x00, 0x01, carry | decimal, true),
+                (0x42, 0x15, decimal, true),
+            ];
+
+            for (a, operand, status, bcd) in cases {
+                let official = run_sbc_immediate(0xE9, a, operand, status, bcd);
+                let unofficial = run_sbc_immediate(0xEB, a, operand, status, bcd);
+                assert_eq!(
+                    official, unofficial,
+                    "A={a:#04x} M={operand:#04x} P={status:#010b}"
+                );
+                assert_eq!(unofficial.2, 2);
+                assert_eq!(unofficial.3, 2);
+            }
+        }
     }
 
     mod cmp_tests {
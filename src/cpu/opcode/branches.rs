@@ -46,6 +46,36 @@ mod branch_tests {
         cpu
     }
 
+    #[test]
+    fn test_branch_not_taken_cycles() {
+        let mut cpu = CPU::new();
+        cpu.load(vec![0xF0, 0x02, 0x00]); // BEQ +2 with Z clear
+
+        assert_eq!(cpu.step(), 2);
+        assert_eq!(cpu.pc, 0x0002);
+    }
+
+    #[test]
+    fn test_branch_taken_same_page_cycles() {
+        let mut cpu = CPU::new();
+        cpu.load(vec![0xD0, 0x02, 0x00]); // BNE +2 with Z clear
+
+        assert_eq!(cpu.step(), 3);
+        assert_eq!(cpu.pc, 0x0004);
+    }
+
+    #[test]
+    fn test_branch_taken_cross_page_cycles() {
+        let mut cpu = CPU::new();
+        cpu.load(vec![0x00]);
+        cpu.mem_write_u8(0x00FD, 0xD0); // BNE +2, lands on 0x0101
+        cpu.mem_write_u8(0x00FE, 0x02);
+        cpu.pc = 0x00FD;
+
+        assert_eq!(cpu.step(), 4);
+        assert_eq!(cpu.pc, 0x0101);
+    }
+
     // BCS (Branch if Carry Set) Tests
     #[test]
     fn test_bcs_carry_set_positive_offset() {
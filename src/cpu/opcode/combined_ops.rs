@@ -106,9 +106,9 @@ mod combined_ops_tests {
             // AND: 202 & 240 = 192 (0b1100_0000)
             // LSR: 192 >> 1 = 96 (0b0110_0000)
             assert_eq!(cpu.reg_a, 0b0110_0000);
-            assert_eq!(cpu.get_flag(StatusFlag::Carry), false); // LSR shifts out 0
-            assert_eq!(cpu.get_flag(StatusFlag::Zero), false);
-            assert_eq!(cpu.get_flag(StatusFlag::Negative), false);
+            assert!(!cpu.get_flag(StatusFlag::Carry)); // LSR shifts out 0
+            assert!(!cpu.get_flag(StatusFlag::Zero));
+            assert!(!cpu.get_flag(StatusFlag::Negative));
             assert_eq!(cpu.pc, 0x0601); // PC should advance by 1 for immediate mode
         }
 
@@ -123,9 +123,9 @@ mod combined_ops_tests {
             // AND: 201 & 255 = 201 (0b1100_1001)
             // LSR: 201 >> 1 = 100 (0b0110_0100)
             assert_eq!(cpu.reg_a, 0b0110_0100);
-            assert_eq!(cpu.get_flag(StatusFlag::Carry), true); // LSR shifts out 1
-            assert_eq!(cpu.get_flag(StatusFlag::Zero), false);
-            assert_eq!(cpu.get_flag(StatusFlag::Negative), false);
+            assert!(cpu.get_flag(StatusFlag::Carry)); // LSR shifts out 1
+            assert!(!cpu.get_flag(StatusFlag::Zero));
+            assert!(!cpu.get_flag(StatusFlag::Negative));
         }
 
         #[test]
@@ -139,9 +139,9 @@ mod combined_ops_tests {
             // AND: 170 & 84 = 0 (0b0000_0000)
             // LSR: 0 >> 1 = 0 (0b0000_0000)
             assert_eq!(cpu.reg_a, 0b0000_0000);
-            assert_eq!(cpu.get_flag(StatusFlag::Carry), false);
-            assert_eq!(cpu.get_flag(StatusFlag::Zero), true);
-            assert_eq!(cpu.get_flag(StatusFlag::Negative), false);
+            assert!(!cpu.get_flag(StatusFlag::Carry));
+            assert!(cpu.get_flag(StatusFlag::Zero));
+            assert!(!cpu.get_flag(StatusFlag::Negative));
         }
 
         #[test]
@@ -176,9 +176,9 @@ mod combined_ops_tests {
 
             // AND: 255 & 129 = 129 (0b1000_0001)
             assert_eq!(cpu.reg_a, 0b1000_0001);
-            assert_eq!(cpu.get_flag(StatusFlag::Negative), true);
-            assert_eq!(cpu.get_flag(StatusFlag::Carry), true); // Carry copied from Negative
-            assert_eq!(cpu.get_flag(StatusFlag::Zero), false);
+            assert!(cpu.get_flag(StatusFlag::Negative));
+            assert!(cpu.get_flag(StatusFlag::Carry)); // Carry copied from Negative
+            assert!(!cpu.get_flag(StatusFlag::Zero));
             assert_eq!(cpu.pc, 0x0601);
         }
 
@@ -192,9 +192,9 @@ mod combined_ops_tests {
 
             // AND: 127 & 126 = 126 (0b0111_1110)
             assert_eq!(cpu.reg_a, 0b0111_1110);
-            assert_eq!(cpu.get_flag(StatusFlag::Negative), false);
-            assert_eq!(cpu.get_flag(StatusFlag::Carry), false); // Carry copied from Negative
-            assert_eq!(cpu.get_flag(StatusFlag::Zero), false);
+            assert!(!cpu.get_flag(StatusFlag::Negative));
+            assert!(!cpu.get_flag(StatusFlag::Carry)); // Carry copied from Negative
+            assert!(!cpu.get_flag(StatusFlag::Zero));
         }
 
         #[test]
@@ -207,9 +207,9 @@ mod combined_ops_tests {
 
             // AND: 170 & 85 = 0 (0b0000_0000)
             assert_eq!(cpu.reg_a, 0b0000_0000);
-            assert_eq!(cpu.get_flag(StatusFlag::Negative), false);
-            assert_eq!(cpu.get_flag(StatusFlag::Carry), false);
-            assert_eq!(cpu.get_flag(StatusFlag::Zero), true);
+            assert!(!cpu.get_flag(StatusFlag::Negative));
+            assert!(!cpu.get_flag(StatusFlag::Carry));
+            assert!(cpu.get_flag(StatusFlag::Zero));
         }
 
         #[test]
@@ -255,8 +255,8 @@ mod combined_ops_tests {
 
             // After AND + ROR, check bits 5 and 6 of result
             // Both bits set: Carry=true, Overflow=false
-            assert_eq!(cpu.get_flag(StatusFlag::Carry), true);
-            assert_eq!(cpu.get_flag(StatusFlag::Overflow), false);
+            assert!(cpu.get_flag(StatusFlag::Carry));
+            assert!(!cpu.get_flag(StatusFlag::Overflow));
         }
 
         #[test]
@@ -269,8 +269,8 @@ mod combined_ops_tests {
             arr(&mut cpu, AddressingMode::Immediate);
 
             // Bit 6 set, bit 5 clear: Carry=true, Overflow=false
-            assert_eq!(cpu.get_flag(StatusFlag::Carry), true);
-            assert_eq!(cpu.get_flag(StatusFlag::Overflow), false);
+            assert!(cpu.get_flag(StatusFlag::Carry));
+            assert!(!cpu.get_flag(StatusFlag::Overflow));
         }
 
         #[test]
@@ -283,8 +283,8 @@ mod combined_ops_tests {
             arr(&mut cpu, AddressingMode::Immediate);
 
             // Bit 5 set, bit 6 clear: Carry=false, Overflow=true
-            assert_eq!(cpu.get_flag(StatusFlag::Carry), false);
-            assert_eq!(cpu.get_flag(StatusFlag::Overflow), true);
+            assert!(!cpu.get_flag(StatusFlag::Carry));
+            assert!(cpu.get_flag(StatusFlag::Overflow));
         }
 
         #[test]
@@ -297,8 +297,8 @@ mod combined_ops_tests {
             arr(&mut cpu, AddressingMode::Immediate);
 
             // Neither bit set: Carry=false, Overflow=false
-            assert_eq!(cpu.get_flag(StatusFlag::Carry), false);
-            assert_eq!(cpu.get_flag(StatusFlag::Overflow), false);
+            assert!(!cpu.get_flag(StatusFlag::Carry));
+            assert!(!cpu.get_flag(StatusFlag::Overflow));
         }
     }
 
@@ -318,9 +318,9 @@ mod combined_ops_tests {
             // AND: 240 & 204 = 192 (0b1100_0000)
             // SUB: 192 - 80 = 112 (0b0111_0000)
             assert_eq!(cpu.reg_x, 112);
-            assert_eq!(cpu.get_flag(StatusFlag::Carry), false); // No borrow (192 >= 80)
-            assert_eq!(cpu.get_flag(StatusFlag::Zero), false);
-            assert_eq!(cpu.get_flag(StatusFlag::Negative), false);
+            assert!(!cpu.get_flag(StatusFlag::Carry)); // No borrow (192 >= 80)
+            assert!(!cpu.get_flag(StatusFlag::Zero));
+            assert!(!cpu.get_flag(StatusFlag::Negative));
             assert_eq!(cpu.pc, 0x0601);
         }
 
@@ -336,9 +336,9 @@ mod combined_ops_tests {
             // AND: 15 & 240 = 0 (0b0000_0000)
             // SUB: 0 - 80 = 176 (0xB0) with underflow
             assert_eq!(cpu.reg_x, 176);
-            assert_eq!(cpu.get_flag(StatusFlag::Carry), true); // Borrow occurred
-            assert_eq!(cpu.get_flag(StatusFlag::Zero), false);
-            assert_eq!(cpu.get_flag(StatusFlag::Negative), true);
+            assert!(cpu.get_flag(StatusFlag::Carry)); // Borrow occurred
+            assert!(!cpu.get_flag(StatusFlag::Zero));
+            assert!(cpu.get_flag(StatusFlag::Negative));
         }
 
         #[test]
@@ -353,9 +353,9 @@ mod combined_ops_tests {
             // AND: 255 & 136 = 136 (0b1000_1000)
             // SUB: 136 - 136 = 0
             assert_eq!(cpu.reg_x, 0);
-            assert_eq!(cpu.get_flag(StatusFlag::Carry), false); // No borrow
-            assert_eq!(cpu.get_flag(StatusFlag::Zero), true);
-            assert_eq!(cpu.get_flag(StatusFlag::Negative), false);
+            assert!(!cpu.get_flag(StatusFlag::Carry)); // No borrow
+            assert!(cpu.get_flag(StatusFlag::Zero));
+            assert!(!cpu.get_flag(StatusFlag::Negative));
         }
 
         #[test]
@@ -401,8 +401,8 @@ mod combined_ops_tests {
 
             assert_eq!(cpu.reg_a, 0x42);
             assert_eq!(cpu.reg_x, 0x42);
-            assert_eq!(cpu.get_flag(StatusFlag::Zero), false);
-            assert_eq!(cpu.get_flag(StatusFlag::Negative), false);
+            assert!(!cpu.get_flag(StatusFlag::Zero));
+            assert!(!cpu.get_flag(StatusFlag::Negative));
             assert_eq!(cpu.pc, 0x0601);
         }
 
@@ -416,8 +416,8 @@ mod combined_ops_tests {
 
             assert_eq!(cpu.reg_a, 0x88);
             assert_eq!(cpu.reg_x, 0x88);
-            assert_eq!(cpu.get_flag(StatusFlag::Zero), false);
-            assert_eq!(cpu.get_flag(StatusFlag::Negative), true); // 0x88 has bit 7 set
+            assert!(!cpu.get_flag(StatusFlag::Zero));
+            assert!(cpu.get_flag(StatusFlag::Negative)); // 0x88 has bit 7 set
             assert_eq!(cpu.pc, 0x0602);
         }
 
@@ -431,8 +431,8 @@ mod combined_ops_tests {
 
             assert_eq!(cpu.reg_a, 0x00);
             assert_eq!(cpu.reg_x, 0x00);
-            assert_eq!(cpu.get_flag(StatusFlag::Zero), true);
-            assert_eq!(cpu.get_flag(StatusFlag::Negative), false);
+            assert!(cpu.get_flag(StatusFlag::Zero));
+            assert!(!cpu.get_flag(StatusFlag::Negative));
         }
 
         #[test]
@@ -445,8 +445,8 @@ mod combined_ops_tests {
 
             assert_eq!(cpu.reg_a, 0xFF);
             assert_eq!(cpu.reg_x, 0xFF);
-            assert_eq!(cpu.get_flag(StatusFlag::Zero), false);
-            assert_eq!(cpu.get_flag(StatusFlag::Negative), true);
+            assert!(!cpu.get_flag(StatusFlag::Zero));
+            assert!(cpu.get_flag(StatusFlag::Negative));
         }
 
         #[test]
@@ -4,10 +4,11 @@ use crate::{
 };
 
 pub(crate) fn inc(cpu: &mut CPU, mode: AddressingMode) {
-    let addr = cpu.get_address(&mode);
-    let value = cpu.mem_read_u8(addr).wrapping_add(1);
-    cpu.mem_write_u8(addr, value);
-    cpu.update_zero_and_negative_flags(value);
+    let addr = cpu.get_rmw_address(&mode);
+    let value = cpu.mem_read_u8(addr);
+    let result = value.wrapping_add(1);
+    cpu.rmw_write(addr, value, result);
+    cpu.update_zero_and_negative_flags(result);
 }
 
 pub(crate) fn inx(cpu: &mut CPU, _mode: AddressingMode) {
@@ -21,10 +22,11 @@ pub(crate) fn iny(cpu: &mut CPU, _mode: AddressingMode) {
 }
 
 pub(crate) fn dec(cpu: &mut CPU, mode: AddressingMode) {
-    let addr = cpu.get_address(&mode);
-    let value = cpu.mem_read_u8(addr).wrapping_sub(1);
-    cpu.mem_write_u8(addr, value);
-    cpu.update_zero_and_negative_flags(value);
+    let addr = cpu.get_rmw_address(&mode);
+    let value = cpu.mem_read_u8(addr);
+    let result = value.wrapping_sub(1);
+    cpu.rmw_write(addr, value, result);
+    cpu.update_zero_and_negative_flags(result);
 }
 pub(crate) fn dex(cpu: &mut CPU, _mode: AddressingMode) {
     cpu.reg_x = cpu.reg_x.wrapping_sub(1);
@@ -444,4 +446,51 @@ mod increment_decrements_tests {
             assert_ne!(cpu.status & (StatusFlag::Negative as u8), 0);
         }
     }
+
+    mod rmw_timing_tests {
+        use super::*;
+        use crate::cpu::opcode::recording_device::step_recorded;
+
+        fn run_indexed(opcode: u8, base: u16, x: u8) -> (u32, Vec<u16>) {
+            let [lo, hi] = base.to_le_bytes();
+            let (cycles, log) = step_recorded(vec![opcode, lo, hi, 0x00], [0; 0x1000], |cpu| {
+                cpu.reg_x = x;
+            });
+            (cycles, log.reads())
+        }
+
+        #[test]
+        fn test_inc_ppudata_writes_twice() {
+            let mut cpu = CPU::new();
+            cpu.load(vec![
+                0xA9, 0x20, 0x8D, 0x06, 0x20, // LDA #$20, STA $2006
+                0xA9, 0x00, 0x8D, 0x06, 0x20, // LDA #$00, STA $2006
+                0xEE, 0x07, 0x20, // INC $2007
+                0x00,
+            ]);
+            for _ in 0..5 {
+                cpu.step();
+            }
+
+            // One read plus the dummy and the final write each bump the address
+            let ppu = cpu.ppu().unwrap();
+            assert_eq!(ppu.vram_addr(), 0x2003);
+            assert_eq!(ppu.vram[1], 0x00); // Dummy write of the buffered value
+            assert_eq!(ppu.vram[2], 0x01);
+        }
+
+        #[test]
+        fn test_inc_absolute_x_dummy_read_on_page_cross() {
+            let (cycles, reads) = run_indexed(0xFE, 0x41F0, 0x20);
+            assert_eq!(cycles, 7);
+            assert_eq!(reads, vec![0x4110, 0x4210]);
+        }
+
+        #[test]
+        fn test_dec_absolute_x_dummy_read_without_page_cross() {
+            let (cycles, reads) = run_indexed(0xDE, 0x4100, 0x20);
+            assert_eq!(cycles, 7);
+            assert_eq!(reads, vec![0x4120, 0x4120]);
+        }
+    }
 }
@@ -19,7 +19,10 @@ pub(crate) fn rts(cpu: &mut CPU, _mode: AddressingMode) {
 #[cfg(test)]
 mod jump_tests {
     use super::*;
-    use crate::{cpu::CPU, mem::Memory};
+    use crate::{
+        cpu::CPU,
+        mem::{Memory, rom::Rom},
+    };
     mod jmp_tests {
         use super::*;
 
@@ -86,6 +89,37 @@ mod jump_tests {
             assert_eq!(cpu.pc, 0x1234);
         }
 
+        #[test]
+        fn test_jmp_indirect_page_boundary_bug_second_page() {
+            let mut cpu = CPU::new();
+            cpu.pc = 0x0600;
+            cpu.mem_write_u16(0x0600, 0x02FF);
+            cpu.mem_write_u8(0x02FF, 0x78); // Low byte of target address
+            cpu.mem_write_u8(0x0200, 0x56); // High byte wraps within the page
+            cpu.mem_write_u8(0x0300, 0x9A); // Not used
+
+            jmp(&mut cpu, AddressingMode::Indirect);
+
+            assert_eq!(cpu.pc, 0x5678);
+        }
+
+        #[test]
+        fn test_jmp_indirect_pointer_in_prg_rom() {
+            // Both pointer bytes are read through the bus from the cartridge
+            let mut prg_rom = vec![0; 0x4000];
+            prg_rom[0x00FF] = 0xCD; // 0x80FF
+            prg_rom[0x0000] = 0xAB; // 0x8000, high byte after the page wrap
+            prg_rom[0x0100] = 0xEF; // 0x8100, not used
+            let mut cpu = CPU::new();
+            cpu.insert_rom(Rom::from_prg(&prg_rom));
+            cpu.pc = 0x0600;
+            cpu.mem_write_u16(0x0600, 0x80FF);
+
+            jmp(&mut cpu, AddressingMode::Indirect);
+
+            assert_eq!(cpu.pc, 0xABCD);
+        }
+
         #[test]
         fn test_jmp_indirect_zero_pointer() {
             let mut cpu = CPU::new();
@@ -7,6 +7,8 @@ pub mod increment_decrements;
 pub mod jumps;
 pub mod load_store;
 pub mod logical;
+#[cfg(test)]
+mod recording_device;
 pub mod register_transfers;
 pub mod rmw;
 pub mod shifts;
@@ -27,6 +29,9 @@ pub struct OP {
 
 impl OP {
     pub fn execute(&self, cpu: &mut CPU) {
+        // The opcode byte has already been fetched, so it sits just behind PC
+        cpu.last_opcode = self.code;
+        cpu.last_opcode_pc = cpu.pc.wrapping_sub(1);
         (self.op)(cpu, self.mode);
     }
 }
@@ -39,6 +44,15 @@ impl From<u8> for OP {
     }
 }
 
+// (code, mnemonic, addressing mode, bytes, base cycles) of every opcode in
+// the table, in opcode order
+pub fn all_opcodes() -> impl Iterator<Item = (u8, &'static str, AddressingMode, u8, u8)> {
+    OPCODE_TABLE
+        .iter()
+        .flatten()
+        .map(|op| (op.code, op.name, op.mode, op.bytes, op.cycles))
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 #[allow(non_camel_case_types)]
 #[allow(dead_code)]
@@ -58,6 +72,47 @@ pub enum AddressingMode {
     NoneAddressing,
 }
 
+impl AddressingMode {
+    pub fn is_indexed(&self) -> bool {
+        self.uses_x() || self.uses_y()
+    }
+
+    pub fn uses_x(&self) -> bool {
+        matches!(
+            self,
+            AddressingMode::ZeroPage_X | AddressingMode::Absolute_X | AddressingMode::Indirect_X
+        )
+    }
+
+    pub fn uses_y(&self) -> bool {
+        matches!(
+            self,
+            AddressingMode::ZeroPage_Y | AddressingMode::Absolute_Y | AddressingMode::Indirect_Y
+        )
+    }
+
+    // The operand lives in memory at an effective address, as opposed to the
+    // instruction stream, the accumulator or a jump/branch target
+    pub fn reads_memory(&self) -> bool {
+        !matches!(
+            self,
+            AddressingMode::Immediate
+                | AddressingMode::Indirect
+                | AddressingMode::Accumulator
+                | AddressingMode::Relative
+                | AddressingMode::NoneAddressing
+        )
+    }
+
+    // Indexing that adds across a 16-bit base and so may carry into the high byte
+    pub fn can_cross_page(&self) -> bool {
+        matches!(
+            self,
+            AddressingMode::Absolute_X | AddressingMode::Absolute_Y | AddressingMode::Indirect_Y
+        )
+    }
+}
+
 #[cfg(test)]
 mod opcode_test {
     use super::*;
@@ -75,4 +130,35 @@ mod opcode_test {
         });
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_all_opcodes() {
+        let opcodes: Vec<_> = all_opcodes().collect();
+        // 151 official opcodes plus 85 unofficial ones
+        assert_eq!(opcodes.len(), 236);
+        assert!(opcodes.contains(&(0xA9, "LDA", AddressingMode::Immediate, 2, 2)));
+        assert!(opcodes.iter().all(|&(code, ..)| code != 0x02));
+        assert!(opcodes.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+
+    #[test]
+    fn test_addressing_mode_predicates() {
+        assert!(AddressingMode::Absolute_X.is_indexed());
+        assert!(AddressingMode::Indirect_Y.is_indexed());
+        assert!(!AddressingMode::Immediate.is_indexed());
+        assert!(!AddressingMode::Indirect.is_indexed());
+
+        assert!(AddressingMode::Indirect_X.uses_x());
+        assert!(!AddressingMode::Indirect_X.uses_y());
+        assert!(AddressingMode::ZeroPage_Y.uses_y());
+
+        assert!(AddressingMode::ZeroPage.reads_memory());
+        assert!(AddressingMode::Indirect_Y.reads_memory());
+        assert!(!AddressingMode::Immediate.reads_memory());
+        assert!(!AddressingMode::Accumulator.reads_memory());
+
+        assert!(AddressingMode::Absolute_Y.can_cross_page());
+        assert!(!AddressingMode::ZeroPage_X.can_cross_page());
+        assert!(!AddressingMode::Indirect_X.can_cross_page());
+    }
 }
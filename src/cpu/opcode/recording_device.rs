@@ -0,0 +1,81 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{cpu::CPU, mem::BusDevice};
+
+// Address range the device is mapped over, clear of the APU/IO registers
+const DEVICE_START: u16 = 0x4020;
+const DEVICE_END: u16 = 0x4FFF;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Access {
+    Read(u16),
+    Write(u16, u8),
+}
+
+// Shared handle to everything the device saw, in bus order
+#[derive(Clone, Default)]
+pub struct AccessLog(Rc<RefCell<Vec<Access>>>);
+
+impl AccessLog {
+    pub fn reads(&self) -> Vec<u16> {
+        self.0
+            .borrow()
+            .iter()
+            .filter_map(|access| match access {
+                Access::Read(addr) => Some(*addr),
+                Access::Write(..) => None,
+            })
+            .collect()
+    }
+
+    pub fn writes(&self) -> Vec<(u16, u8)> {
+        self.0
+            .borrow()
+            .iter()
+            .filter_map(|access| match access {
+                Access::Write(addr, data) => Some((*addr, *data)),
+                Access::Read(_) => None,
+            })
+            .collect()
+    }
+}
+
+// Plain RAM indexed from $4000 that logs every access
+pub struct RecordingDevice {
+    data: [u8; 0x1000],
+    log: AccessLog,
+}
+
+impl BusDevice for RecordingDevice {
+    fn read(&mut self, addr: u16) -> u8 {
+        self.log.0.borrow_mut().push(Access::Read(addr));
+        self.data[(addr - 0x4000) as usize]
+    }
+
+    fn write(&mut self, addr: u16, data: u8) {
+        self.log.0.borrow_mut().push(Access::Write(addr, data));
+        self.data[(addr - 0x4000) as usize] = data;
+    }
+}
+
+// Loads `program`, maps a recording device holding `data`, lets `setup`
+// prepare registers and memory, then steps one instruction
+pub fn step_recorded<F>(program: Vec<u8>, data: [u8; 0x1000], setup: F) -> (u32, AccessLog)
+where
+    F: FnOnce(&mut CPU),
+{
+    let mut cpu = CPU::new();
+    cpu.load(program);
+    let log = AccessLog::default();
+    cpu.bus.register_device(
+        DEVICE_START..=DEVICE_END,
+        Box::new(RecordingDevice {
+            data,
+            log: log.clone(),
+        }),
+    );
+    setup(&mut cpu);
+
+    let cycles = cpu.step();
+    (cycles, log)
+}
@@ -141,6 +141,44 @@ mod transfer_test {
         assert_eq!(cpu.status, original_status); // Status should be unchanged
     }
 
+    #[test]
+    fn test_0x9a_txs_negative_value_leaves_flags() {
+        let mut cpu = CPU::new();
+        cpu.status = 0b0000_0000;
+        cpu.reg_x = 0x80;
+
+        txs(&mut cpu, AddressingMode::NoneAddressing);
+
+        assert_eq!(cpu.stack, 0x80);
+        assert!(cpu.status & 0b1000_0000 == 0);
+        assert!(cpu.status & 0b0000_0010 == 0);
+    }
+
+    #[test]
+    fn test_0xba_tsx_negative_value_sets_flag() {
+        let mut cpu = CPU::new();
+        cpu.status = 0b0000_0000;
+        cpu.stack = 0x80;
+
+        tsx(&mut cpu, AddressingMode::NoneAddressing);
+
+        assert_eq!(cpu.reg_x, 0x80);
+        assert!(cpu.status & 0b1000_0000 != 0);
+        assert!(cpu.status & 0b0000_0010 == 0);
+    }
+
+    #[test]
+    fn test_0xba_tsx_zero_flag() {
+        let mut cpu = CPU::new();
+        cpu.status = 0b1000_0000;
+        cpu.stack = 0x00;
+
+        tsx(&mut cpu, AddressingMode::NoneAddressing);
+
+        assert!(cpu.status & 0b0000_0010 != 0);
+        assert!(cpu.status & 0b1000_0000 == 0);
+    }
+
     #[test]
     fn test_0x98_tya_transfer() {
         let mut cpu = CPU::new();
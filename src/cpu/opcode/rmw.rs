@@ -7,9 +7,10 @@ use crate::{
 };
 
 pub(crate) fn dcp(cpu: &mut CPU, mode: AddressingMode) {
-    let addr = cpu.get_address(&mode);
-    let value = cpu.mem_read_u8(addr).wrapping_sub(1);
-    cpu.mem_write_u8(addr, value);
+    let addr = cpu.get_rmw_address(&mode);
+    let old = cpu.mem_read_u8(addr);
+    let value = old.wrapping_sub(1);
+    cpu.rmw_write(addr, old, value);
     cpu.set_flag(StatusFlag::Carry, cpu.reg_a >= value);
     cpu.set_flag(StatusFlag::Zero, cpu.reg_a == value);
     cpu.set_flag(
@@ -19,20 +20,21 @@ pub(crate) fn dcp(cpu: &mut CPU, mode: AddressingMode) {
 }
 
 pub(crate) fn isc(cpu: &mut CPU, mode: AddressingMode) {
-    let addr = cpu.get_address(&mode);
-    let value = cpu.mem_read_u8(addr).wrapping_add(1);
-    cpu.mem_write_u8(addr, value);
+    let addr = cpu.get_rmw_address(&mode);
+    let old = cpu.mem_read_u8(addr);
+    let value = old.wrapping_add(1);
+    cpu.rmw_write(addr, old, value);
     cpu_addition_with_carry(cpu, value ^ 0xFF);
 }
 
 pub(crate) fn rla(cpu: &mut CPU, mode: AddressingMode) {
-    let addr = cpu.get_address(&mode);
+    let addr = cpu.get_rmw_address(&mode);
     let value = cpu.mem_read_u8(addr);
     let mut result = value << 1;
     if cpu.get_flag(StatusFlag::Carry) {
-        result += 1
+        result |= 0b0000_0001;
     }
-    cpu.mem_write_u8(addr, result);
+    cpu.rmw_write(addr, value, result);
 
     cpu.reg_a &= result;
     cpu.set_flag(StatusFlag::Carry, (value & 0b1000_0000) != 0);
@@ -40,22 +42,22 @@ pub(crate) fn rla(cpu: &mut CPU, mode: AddressingMode) {
 }
 
 pub(crate) fn rra(cpu: &mut CPU, mode: AddressingMode) {
-    let addr = cpu.get_address(&mode);
+    let addr = cpu.get_rmw_address(&mode);
     let value = cpu.mem_read_u8(addr);
     let mut result = value >> 1;
     if cpu.get_flag(StatusFlag::Carry) {
-        result += 0b1000_0000;
+        result |= 0b1000_0000;
     }
-    cpu.mem_write_u8(addr, result);
+    cpu.rmw_write(addr, value, result);
     cpu.set_flag(StatusFlag::Carry, value & 0b0000_0001 != 0);
     cpu_addition_with_carry(cpu, result);
 }
 
 pub(crate) fn slo(cpu: &mut CPU, mode: AddressingMode) {
-    let addr = cpu.get_address(&mode);
+    let addr = cpu.get_rmw_address(&mode);
     let value = cpu.mem_read_u8(addr);
     let result = value << 1;
-    cpu.mem_write_u8(addr, result);
+    cpu.rmw_write(addr, value, result);
 
     cpu.reg_a |= result;
     cpu.set_flag(StatusFlag::Carry, (value & 0b1000_0000) != 0);
@@ -63,10 +65,10 @@ pub(crate) fn slo(cpu: &mut CPU, mode: AddressingMode) {
 }
 
 pub(crate) fn sre(cpu: &mut CPU, mode: AddressingMode) {
-    let addr = cpu.get_address(&mode);
+    let addr = cpu.get_rmw_address(&mode);
     let value = cpu.mem_read_u8(addr);
     let result = value >> 1;
-    cpu.mem_write_u8(addr, result);
+    cpu.rmw_write(addr, value, result);
 
     cpu.reg_a ^= result;
     cpu.set_flag(StatusFlag::Carry, value & 0b0000_0001 != 0);
@@ -145,6 +147,46 @@ mod rmw_tests {
             assert_eq!(cpu.status & StatusFlag::Zero as u8, 0);
             assert_eq!(cpu.status & StatusFlag::Negative as u8, 0); // Result of 0x50 - 0xFF is positive (0x51)
         }
+
+        #[test]
+        fn test_dcp_matches_dec_then_cmp() {
+            use crate::cpu::opcode::{arithmetic::cmp, increment_decrements::dec};
+
+            let setup = |a: u8, m: u8| {
+                let mut cpu = CPU::new();
+                cpu.reg_a = a;
+                cpu.pc = 0x0600;
+                cpu.mem_write_u8(0x0600, 0x10);
+                cpu.mem_write_u8(0x10, m);
+                cpu
+            };
+
+            for (a, m) in [
+                (0x05, 0x03),
+                (0x04, 0x05),
+                (0x02, 0x05),
+                (0x50, 0x00),
+                (0x00, 0x01),
+                (0x80, 0x01),
+                (0xFF, 0x80),
+            ] {
+                let mut combined = setup(a, m);
+                dcp(&mut combined, AddressingMode::ZeroPage);
+
+                let mut separate = setup(a, m);
+                dec(&mut separate, AddressingMode::ZeroPage);
+                separate.pc = 0x0600;
+                cmp(&mut separate, AddressingMode::ZeroPage);
+
+                assert_eq!(combined.reg_a, a, "A changed for {:02X}/{:02X}", a, m);
+                assert_eq!(
+                    combined.status, separate.status,
+                    "flags for {:02X}/{:02X}",
+                    a, m
+                );
+                assert_eq!(combined.mem_read_u8(0x10), separate.mem_read_u8(0x10));
+            }
+        }
     }
 
     // ISC (INC + SBC) Tests
@@ -264,6 +306,24 @@ mod rmw_tests {
             assert_eq!(cpu.reg_a, 0x00);
             assert_ne!(cpu.status & StatusFlag::Zero as u8, 0); // Zero flag set
         }
+
+        #[test]
+        fn test_rla_all_ones_with_carry_in() {
+            let mut cpu = CPU::new();
+            cpu.reg_a = 0xFF;
+            cpu.set_flag(StatusFlag::Carry, true);
+            cpu.pc = 0x0600;
+            cpu.mem_write_u8(0x0600, 0x10);
+            cpu.mem_write_u8(0x10, 0xFF); // Stays 0xFF after ROL with carry in
+
+            rla(&mut cpu, AddressingMode::ZeroPage);
+
+            assert_eq!(cpu.mem_read_u8(0x10), 0xFF);
+            assert_eq!(cpu.reg_a, 0xFF);
+            assert_ne!(cpu.status & StatusFlag::Carry as u8, 0); // Original bit 7 was 1
+            assert_eq!(cpu.status & StatusFlag::Zero as u8, 0);
+            assert_ne!(cpu.status & StatusFlag::Negative as u8, 0);
+        }
     }
 
     // RRA (ROR + ADC) Tests
@@ -334,6 +394,26 @@ mod rmw_tests {
             // 0xF0 + 0x7F + 1(carry from rotation) = 0x170, which sets carry flag
             assert_ne!(cpu.status & StatusFlag::Carry as u8, 0); // Carry from addition
         }
+
+        #[test]
+        fn test_rra_all_ones_with_carry_in() {
+            let mut cpu = CPU::new();
+            cpu.reg_a = 0x00;
+            cpu.set_flag(StatusFlag::Carry, true);
+            cpu.pc = 0x0600;
+            cpu.mem_write_u8(0x0600, 0x10);
+            cpu.mem_write_u8(0x10, 0xFF); // Stays 0xFF after ROR with carry in
+
+            rra(&mut cpu, AddressingMode::ZeroPage);
+
+            assert_eq!(cpu.mem_read_u8(0x10), 0xFF);
+            // 0x00 + 0xFF + 1(carry from rotation) = 0x100
+            assert_eq!(cpu.reg_a, 0x00);
+            assert_ne!(cpu.status & StatusFlag::Carry as u8, 0);
+            assert_ne!(cpu.status & StatusFlag::Zero as u8, 0);
+            assert_eq!(cpu.status & StatusFlag::Negative as u8, 0);
+            assert_eq!(cpu.status & StatusFlag::Overflow as u8, 0);
+        }
     }
 
     // SLO (ASL + ORA) Tests
@@ -486,4 +566,50 @@ mod rmw_tests {
         assert_ne!(cpu.status & StatusFlag::Carry as u8, 0); // 0xFF >= 0xFE
         assert_eq!(cpu.status & StatusFlag::Zero as u8, 0);
     }
+
+    mod rmw_timing_tests {
+        use crate::{cpu::opcode::recording_device::step_recorded, mem::Memory};
+
+        fn run(program: Vec<u8>) -> (u32, Vec<(u16, u8)>) {
+            let mut data = [0; 0x1000];
+            data[0x0101] = 0b0100_0001;
+            let (cycles, log) = step_recorded(program, data, |cpu| {
+                cpu.mem_write_u16(0x0010, 0x40F0); // Pointer for (zp),Y
+                cpu.reg_x = 0x11;
+                cpu.reg_y = 0x11;
+            });
+            (cycles, log.writes())
+        }
+
+        #[test]
+        fn test_illegal_rmw_writes_twice() {
+            // Absolute,X / Absolute,Y / (Indirect),Y opcodes of SLO, SRE, RLA, RRA, DCP, ISC
+            let cases = [
+                (0x1F, 0x1B, 0x13, 0b1000_0010), // SLO: ASL
+                (0x5F, 0x5B, 0x53, 0b0010_0000), // SRE: LSR
+                (0x3F, 0x3B, 0x33, 0b1000_0010), // RLA: ROL, carry clear
+                (0x7F, 0x7B, 0x73, 0b0010_0000), // RRA: ROR, carry clear
+                (0xDF, 0xDB, 0xD3, 0b0100_0000), // DCP: DEC
+                (0xFF, 0xFB, 0xF3, 0b0100_0010), // ISC: INC
+            ];
+
+            for (abs_x, abs_y, ind_y, result) in cases {
+                for (program, expected_cycles) in [
+                    (vec![abs_x, 0xF0, 0x40, 0x00], 7),
+                    (vec![abs_y, 0xF0, 0x40, 0x00], 7),
+                    (vec![ind_y, 0x10, 0x00], 8),
+                ] {
+                    let opcode = program[0];
+                    let (cycles, writes) = run(program);
+                    assert_eq!(cycles, expected_cycles, "opcode {:02X}", opcode);
+                    assert_eq!(
+                        writes,
+                        vec![(0x4101, 0b0100_0001), (0x4101, result)],
+                        "opcode {:02X}",
+                        opcode
+                    );
+                }
+            }
+        }
+    }
 }
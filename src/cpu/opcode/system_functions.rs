@@ -1,20 +1,30 @@
 use crate::{
     cpu::{CPU, StatusFlag, opcode::AddressingMode},
+    mem::Memory,
     utils::set_bit,
 };
 
 pub(crate) fn brk(cpu: &mut CPU, _mode: AddressingMode) {
+    if cpu.brk_interrupts {
+        cpu.interrupt_brk();
+        return;
+    }
     cpu.status = set_bit(cpu.status, StatusFlag::Break as u8, true);
 }
 
 pub(crate) fn nop(cpu: &mut CPU, mode: AddressingMode) {
-    cpu.try_get_address(&mode);
+    // The unofficial NOPs with an operand still perform the read
+    if let Some(addr) = cpu.try_get_address(&mode) {
+        cpu.mem_read_u8(addr);
+        cpu.add_page_cross_cycle();
+    }
 }
 
 pub(crate) fn rti(cpu: &mut CPU, _mode: AddressingMode) {
+    // B only exists on the stack: the pulled copy is dropped and bit 5 reads as 1
     let value = cpu.stack_pull_value_u8();
-    cpu.status &= 0b0011_0000; // Clear all flags except B and extra bit
-    cpu.status |= value & 0b1100_1111; // The B flag and extra bit are ignored.
+    cpu.status = (value & 0b1100_1111) | StatusFlag::Break2 as u8;
+    // Unlike RTS the pulled address is the return address itself
     cpu.pc = cpu.stack_pull_value_u16();
 }
 
@@ -33,6 +43,46 @@ mod system_functions_tests {
         assert_ne!(cpu.status & StatusFlag::Break as u8, 0);
     }
 
+    // BRK at 0x0000, NMI handler at 0x0300, IRQ/BRK handler at 0x0400
+    fn create_brk_cpu() -> CPU {
+        let mut cpu = CPU::new();
+        cpu.load(vec![0x00, 0xEA]);
+        cpu.load_at(vec![0x00, 0x03, 0x00, 0x00, 0x00, 0x04], 0xFFFA);
+        cpu.brk_interrupts = true;
+        cpu
+    }
+
+    #[test]
+    fn test_brk_interrupt_vectors_through_irq() {
+        let mut cpu = create_brk_cpu();
+
+        assert_eq!(cpu.step(), 7);
+
+        assert_eq!(cpu.pc, 0x0400);
+        assert_eq!(cpu.stack, 0xFC);
+        assert_eq!(cpu.mem_read_u16(0x01FE), 0x0002); // Return skips the padding byte
+        assert_ne!(cpu.mem_read_u8(0x01FD) & StatusFlag::Break as u8, 0);
+        assert_eq!(cpu.status & StatusFlag::Break as u8, 0);
+        assert_ne!(cpu.status & StatusFlag::InterruptDisable as u8, 0);
+    }
+
+    #[test]
+    fn test_brk_hijacked_by_nmi() {
+        let mut cpu = create_brk_cpu();
+        cpu.mem_write_u8(0x2000, 0b1000_0000); // Enable NMI on VBlank
+        // VBlank starts two CPU cycles into the BRK
+        cpu.bus.tick_ppu(241 * 341 - 6);
+
+        cpu.step();
+
+        // Vectored through the NMI handler but still pushed the BRK state
+        assert_eq!(cpu.pc, 0x0300);
+        assert_eq!(cpu.mem_read_u16(0x01FE), 0x0002);
+        assert_ne!(cpu.mem_read_u8(0x01FD) & StatusFlag::Break as u8, 0);
+        // The NMI was consumed by the hijack
+        assert!(!cpu.bus.tick_ppu(0));
+    }
+
     #[test]
     fn test_brk_preserves_other_flags() {
         let mut cpu = CPU::new();
@@ -183,6 +233,36 @@ mod system_functions_tests {
         assert_eq!(cpu.stack, initial_stack);
     }
 
+    #[test]
+    fn test_nop_absolute_x_page_cross_cycle() {
+        let mut cpu = CPU::new();
+        // *NOP $02FF,X; *NOP $0200,X
+        cpu.load(vec![0x1C, 0xFF, 0x02, 0x1C, 0x00, 0x02, 0x00]);
+        cpu.reg_x = 0x01;
+
+        assert_eq!(cpu.step(), 5); // 0x02FF + 1 crosses into page 0x03
+        assert_eq!(cpu.step(), 4);
+    }
+
+    #[test]
+    fn test_nop_absolute_x_performs_read() {
+        let mut cpu = CPU::new();
+        cpu.load_and_run(vec![
+            0xA9, 0x20, 0x8D, 0x06, 0x20, // LDA #$20, STA $2006
+            0xA9, 0x00, 0x8D, 0x06, 0x20, // LDA #$00, STA $2006
+            0xA9, 0xAA, 0x8D, 0x07, 0x20, // LDA #$AA, STA $2007
+            0xA9, 0x20, 0x8D, 0x06, 0x20, // LDA #$20, STA $2006
+            0xA9, 0x00, 0x8D, 0x06, 0x20, // LDA #$00, STA $2006
+            0xA2, 0x08, // LDX #$08
+            0x1C, 0xFF, 0x20, // *NOP $20FF,X reads $2107, a mirror of PPUDATA
+            0xAD, 0x07, 0x20, // LDA $2007
+            0x00,
+        ]);
+
+        // The NOP read primed the PPUDATA buffer with the byte at $2000
+        assert_eq!(cpu.reg_a, 0xAA);
+    }
+
     #[test]
     fn test_nop_addressing_modes_preserve_state() {
         let mut cpu = CPU::new();
@@ -243,8 +323,7 @@ mod system_functions_tests {
 
         rti(&mut cpu, AddressingMode::NoneAddressing);
 
-        // Status should be restored with bits 4 and 5 preserved from current status
-        // Original: 0b1010_1010, but bits 4,5 are preserved (initially 0), so result is 0b1010_1010
+        // Status should be restored with bit 5 set and B clear
         assert_eq!(cpu.status, 0b1010_1010);
         // PC should be restored
         assert_eq!(cpu.pc, 0x3456);
@@ -263,9 +342,23 @@ mod system_functions_tests {
 
         rti(&mut cpu, AddressingMode::NoneAddressing);
 
-        // B flag and extra bit from stack are ignored, but current CPU's bits 4,5 are preserved
-        // CPU starts with status 0b00100100, so bit 5 (value 32) is preserved
-        assert_eq!(cpu.status, 0b0010_0000); // Only bit 5 preserved from initial CPU status
+        // B flag and extra bit from stack are ignored, bit 5 always reads as set
+        assert_eq!(cpu.status, 0b0010_0000);
+    }
+
+    #[test]
+    fn test_rti_forces_bit_5_and_clears_break() {
+        let mut cpu = CPU::new();
+        cpu.status = 0b0001_0000; // B set, bit 5 clear before the RTI
+
+        cpu.stack = 0xFC;
+        cpu.mem_write_u8(0x01FD, 0b0101_0011); // Pulled B set, bit 5 clear
+        cpu.mem_write_u16(0x01FE, 0x8123);
+
+        rti(&mut cpu, AddressingMode::NoneAddressing);
+
+        assert_eq!(cpu.status, 0b0110_0011);
+        assert_eq!(cpu.pc, 0x8123); // No +1 as with RTS
     }
 
     #[test]
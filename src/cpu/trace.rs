@@ -0,0 +1,109 @@
+use std::io::{self, Write};
+
+use crate::cpu::{CPU, StatusFlag};
+
+// Writes one nestest-style line per instruction, taken before it executes.
+// Meant to be called from `CPU::run_with_callback`
+pub struct Tracer<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> Tracer<W> {
+    pub fn new(writer: W) -> Self {
+        Tracer { writer }
+    }
+
+    pub fn trace(&mut self, cpu: &mut CPU) -> io::Result<()> {
+        writeln!(self.writer, "{}", cpu.print_state())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+// Runs `cpu` one instruction per line of `reference`, a nestest-style log, and
+// stops at the first line that differs. Errors carry the 1-based line number,
+// the generated line and the expected one. Columns past the end of the
+// generated line, like the PPU/CYC counters of nestest.log, are not compared
+pub fn compare_trace(cpu: &mut CPU, reference: &str) -> Result<(), (usize, String, String)> {
+    for (index, expected) in reference.lines().enumerate() {
+        if cpu.status & StatusFlag::Break as u8 != 0 {
+            return Err((index + 1, String::new(), expected.to_string()));
+        }
+        let generated = cpu.print_state();
+        let compared = expected.get(..generated.len()).unwrap_or(expected);
+        if generated.trim_end() != compared.trim_end() {
+            return Err((index + 1, generated, expected.to_string()));
+        }
+        cpu.step();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod trace_tests {
+    use super::*;
+
+    #[test]
+    fn test_tracer_writes_one_line_per_instruction() {
+        let mut cpu = traced_program();
+
+        let mut tracer = Tracer::new(Vec::new());
+        cpu.run_with_callback(|cpu| tracer.trace(cpu).unwrap());
+
+        let log = String::from_utf8(tracer.into_inner()).unwrap();
+        let lines: Vec<&str> = log.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("0000  A9 01     LDA #$01"));
+        assert!(lines[1].starts_with("0002  A2 02     LDX #$02"));
+        assert!(lines[2].starts_with("0004  00        BRK"));
+        for line in &lines {
+            assert!(line.contains(" A:") && line.contains(" X:") && line.contains(" SP:"));
+        }
+        assert!(lines[1].contains("A:01 X:00"));
+    }
+
+    fn traced_program() -> CPU {
+        let mut cpu = CPU::new();
+        cpu.load(vec![0xA9, 0x01, 0xA2, 0x02, 0x00]); // LDA #$01; LDX #$02; BRK
+        cpu
+    }
+
+    fn reference_log() -> String {
+        let mut tracer = Tracer::new(Vec::new());
+        traced_program().run_with_callback(|cpu| tracer.trace(cpu).unwrap());
+        String::from_utf8(tracer.into_inner()).unwrap()
+    }
+
+    #[test]
+    fn test_compare_trace_matching() {
+        let mut reference: Vec<String> = reference_log().lines().map(String::from).collect();
+        reference[0].push_str(" PPU:  0, 21 CYC:7"); // Extra nestest.log columns
+        let reference = reference.join("\n");
+
+        assert_eq!(compare_trace(&mut traced_program(), &reference), Ok(()));
+    }
+
+    #[test]
+    fn test_compare_trace_mismatch() {
+        let reference = reference_log();
+        let expected = reference.lines().nth(2).unwrap().replace("X:02", "X:03");
+        let reference = reference.replace("X:02", "X:03");
+
+        let (line, generated, reported) =
+            compare_trace(&mut traced_program(), &reference).unwrap_err();
+        assert_eq!(line, 3);
+        assert!(generated.contains("BRK") && generated.contains("X:02"));
+        assert_eq!(reported, expected);
+    }
+
+    #[test]
+    fn test_compare_trace_reference_longer_than_run() {
+        let reference = format!("{}{}", reference_log(), "0005  EA        NOP");
+
+        let (line, generated, _) = compare_trace(&mut traced_program(), &reference).unwrap_err();
+        assert_eq!(line, 4);
+        assert!(generated.is_empty());
+    }
+}
@@ -0,0 +1,106 @@
+use bitflags::bitflags;
+
+bitflags! {
+  // Bit order matches the serial read order of the standard controller
+  #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+  pub struct JoypadButton: u8 {
+    const BUTTON_A = 0b00000001;
+    const BUTTON_B = 0b00000010;
+    const SELECT   = 0b00000100;
+    const START    = 0b00001000;
+    const UP       = 0b00010000;
+    const DOWN     = 0b00100000;
+    const LEFT     = 0b01000000;
+    const RIGHT    = 0b10000000;
+  }
+}
+
+#[derive(Clone)]
+pub struct Joypad {
+    strobe: bool,
+    button_index: u8,
+    button_status: JoypadButton,
+}
+
+impl Default for Joypad {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Joypad {
+    pub fn new() -> Self {
+        Joypad {
+            strobe: false,
+            button_index: 0,
+            button_status: JoypadButton::empty(),
+        }
+    }
+
+    pub fn write(&mut self, data: u8) {
+        self.strobe = data & 1 == 1;
+        if self.strobe {
+            self.button_index = 0;
+        }
+    }
+
+    pub fn read(&mut self) -> u8 {
+        // After all eight buttons have been shifted out the port reads 1
+        if self.button_index > 7 {
+            return 1;
+        }
+        let response = (self.button_status.bits() >> self.button_index) & 1;
+        if !self.strobe {
+            self.button_index += 1;
+        }
+        response
+    }
+
+    pub fn set_button_pressed_status(&mut self, button: JoypadButton, pressed: bool) {
+        self.button_status.set(button, pressed);
+    }
+
+    pub fn set_buttons(&mut self, buttons: JoypadButton) {
+        self.button_status = buttons;
+    }
+
+    pub fn buttons(&self) -> JoypadButton {
+        self.button_status
+    }
+}
+
+#[cfg(test)]
+mod joypad_tests {
+    use super::*;
+
+    #[test]
+    fn test_strobe_mode() {
+        let mut joypad = Joypad::new();
+        joypad.write(1);
+        joypad.set_button_pressed_status(JoypadButton::BUTTON_A, true);
+
+        // While strobing the A button is read repeatedly
+        for _ in 0..10 {
+            assert_eq!(joypad.read(), 1);
+        }
+    }
+
+    #[test]
+    fn test_serial_read_order() {
+        let mut joypad = Joypad::new();
+        joypad.set_buttons(JoypadButton::RIGHT | JoypadButton::SELECT | JoypadButton::BUTTON_A);
+        joypad.write(1);
+        joypad.write(0);
+
+        let bits: Vec<u8> = (0..8).map(|_| joypad.read()).collect();
+        assert_eq!(bits, vec![1, 0, 1, 0, 0, 0, 0, 1]);
+        // Reads past the eighth button return 1
+        assert_eq!(joypad.read(), 1);
+
+        // Strobing again restarts from A
+        joypad.write(1);
+        joypad.write(0);
+        assert_eq!(joypad.read(), 1);
+        assert_eq!(joypad.read(), 0);
+    }
+}
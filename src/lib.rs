@@ -1,4 +1,9 @@
+pub mod apu;
+pub mod console;
 pub mod cpu;
+pub mod joypad;
 pub mod mem;
 pub mod ppu;
+pub mod region;
+pub mod render;
 pub mod utils;
@@ -1,19 +1,78 @@
+use std::{collections::HashMap, ops::RangeInclusive};
+
 use crate::{
-    mem::{Memory, rom::Rom},
+    apu::APU,
+    joypad::Joypad,
+    mem::{BusDevice, Memory, prg_ram::PrgRam, rom::Rom},
     ppu::PPU,
+    region::Region,
 };
 
 const RAM_START: u16 = 0x0000;
 const RAM_END: u16 = 0x1FFF;
 const PPU_START: u16 = 0x2000;
 const PPU_END: u16 = 0x3FFF;
+const APU_CHANNELS_START: u16 = 0x4000;
+const APU_CHANNELS_END: u16 = 0x400F;
+const DMC_START: u16 = 0x4010;
+const DMC_END: u16 = 0x4013;
+const OAM_DMA: u16 = 0x4014;
+const APU_STATUS: u16 = 0x4015;
+const JOYPAD_1: u16 = 0x4016;
+const FRAME_COUNTER: u16 = 0x4017;
+const PRG_RAM_START: u16 = 0x6000;
+const PRG_RAM_END: u16 = 0x7FFF;
+#[cfg(feature = "open-bus-decay")]
+const OPEN_BUS_DECAY_CYCLES: u32 = 1_073_864; // Roughly 600ms of NTSC CPU time
 const PRG_START: u16 = 0x8000;
 const END: u16 = 0xFFFF;
 
 pub struct Bus {
     cpu_ram: [u8; 2048],
+    prg_ram: PrgRam,
     rom: Option<Rom>,
     ppu: Option<PPU>,
+    apu: APU,
+    joypad1: Joypad,
+    frame_complete: bool,
+    prg_ram_dirty: bool,
+    devices: Vec<(RangeInclusive<u16>, Box<dyn BusDevice>)>,
+    patches: HashMap<u16, (u8, Option<u8>)>, // Value and optional compare byte
+    region: Region,
+    dot_fifths: u32, // Fraction of a PPU dot carried over between ticks (PAL)
+    ppu_dots: u64,   // PPU dots elapsed since power-on
+    cpu_cycles: u64,
+    oam_dma_page: Option<u8>, // $4014 was written, the copy starts after the instruction
+    dma_stall: u32,           // CPU cycles the running DMA still halts the CPU for
+    #[cfg(feature = "open-bus-decay")]
+    open_bus: OpenBus,
+}
+
+// Last value driven on the data bus, which unmapped reads see until it decays
+#[cfg(feature = "open-bus-decay")]
+#[derive(Clone)]
+struct OpenBus {
+    value: u8,
+    age: u32,
+    decay_cycles: u32,
+}
+
+// Mutable bus state for save states. The cartridge ROM never changes and only
+// NROM is wired in, so there is no mapper bank state; devices are not saved
+#[derive(Clone)]
+pub struct BusState {
+    cpu_ram: [u8; 2048],
+    prg_ram: PrgRam,
+    ppu: Option<PPU>,
+    apu: APU,
+    joypad1: Joypad,
+    region: Region,
+    dot_fifths: u32,
+    ppu_dots: u64,
+    cpu_cycles: u64,
+    dma_stall: u32,
+    #[cfg(feature = "open-bus-decay")]
+    open_bus: OpenBus,
 }
 
 impl Default for Bus {
@@ -26,46 +85,341 @@ impl Bus {
     pub fn new() -> Self {
         Bus {
             cpu_ram: [0; 2048],
+            prg_ram: PrgRam::default(),
             rom: None,
             ppu: None,
+            apu: APU::new(),
+            joypad1: Joypad::new(),
+            frame_complete: false,
+            prg_ram_dirty: false,
+            devices: Vec::new(),
+            patches: HashMap::new(),
+            region: Region::Ntsc,
+            dot_fifths: 0,
+            ppu_dots: 0,
+            cpu_cycles: 0,
+            oam_dma_page: None,
+            dma_stall: 0,
+            #[cfg(feature = "open-bus-decay")]
+            open_bus: OpenBus {
+                value: 0,
+                age: 0,
+                decay_cycles: OPEN_BUS_DECAY_CYCLES,
+            },
         }
     }
 
     pub fn from_rom(rom: Rom) -> Self {
-        let ppu = PPU::new(rom.chr_rom.clone(), rom.screen_mirroring);
+        let ppu = Self::create_ppu(&rom);
         Bus {
             cpu_ram: [0; 2048],
+            prg_ram: PrgRam::new(rom.sram_size()),
             rom: Some(rom),
             ppu: Some(ppu),
+            apu: APU::new(),
+            joypad1: Joypad::new(),
+            frame_complete: false,
+            prg_ram_dirty: false,
+            devices: Vec::new(),
+            patches: HashMap::new(),
+            region: Region::Ntsc,
+            dot_fifths: 0,
+            ppu_dots: 0,
+            cpu_cycles: 0,
+            oam_dma_page: None,
+            dma_stall: 0,
+            #[cfg(feature = "open-bus-decay")]
+            open_bus: OpenBus {
+                value: 0,
+                age: 0,
+                decay_cycles: OPEN_BUS_DECAY_CYCLES,
+            },
         }
     }
 
+    fn create_ppu(rom: &Rom) -> PPU {
+        let mut ppu = PPU::new(rom.chr_rom.clone(), rom.screen_mirroring);
+        ppu.chr_is_ram = rom.chr_is_ram;
+        ppu
+    }
+
     pub fn insert_rom(&mut self, rom: Rom) {
-        let ppu = PPU::new(rom.chr_rom.clone(), rom.screen_mirroring);
+        let mut ppu = Self::create_ppu(&rom);
+        ppu.set_region(self.region);
+        // A new cartridge never sees the previous one's save RAM
+        self.prg_ram = PrgRam::new(rom.sram_size());
         self.rom = Some(rom);
         self.ppu = Some(ppu);
     }
 
-    pub fn tick(&mut self, count: u32) {
+    // Maps `device` over `range`; devices take priority over the built-in
+    // memory map and the first registered device wins on overlap
+    pub fn register_device(&mut self, range: RangeInclusive<u16>, device: Box<dyn BusDevice>) {
+        self.devices.push((range, device));
+    }
+
+    // Overrides CPU reads of `addr` without touching the underlying memory,
+    // which is how compare-less Game Genie codes work
+    pub fn patch(&mut self, addr: u16, value: u8) {
+        self.patches.insert(addr, (value, None));
+    }
+
+    // Only overrides while the real byte equals `compare`, so an 8-letter
+    // code leaves other banks mapped at `addr` alone
+    pub fn patch_compare(&mut self, addr: u16, value: u8, compare: u8) {
+        self.patches.insert(addr, (value, Some(compare)));
+    }
+
+    pub fn clear_patch(&mut self, addr: u16) {
+        self.patches.remove(&addr);
+    }
+
+    pub fn save_state(&self) -> BusState {
+        BusState {
+            cpu_ram: self.cpu_ram,
+            prg_ram: self.prg_ram.clone(),
+            ppu: self.ppu.clone(),
+            apu: self.apu.clone(),
+            joypad1: self.joypad1.clone(),
+            region: self.region,
+            dot_fifths: self.dot_fifths,
+            ppu_dots: self.ppu_dots,
+            cpu_cycles: self.cpu_cycles,
+            dma_stall: self.dma_stall,
+            #[cfg(feature = "open-bus-decay")]
+            open_bus: self.open_bus.clone(),
+        }
+    }
+
+    pub fn load_state(&mut self, state: &BusState) {
+        self.cpu_ram = state.cpu_ram;
+        self.prg_ram = state.prg_ram.clone();
+        self.ppu = state.ppu.clone();
+        self.apu = state.apu.clone();
+        self.joypad1 = state.joypad1.clone();
+        self.region = state.region;
+        self.dot_fifths = state.dot_fifths;
+        self.ppu_dots = state.ppu_dots;
+        self.cpu_cycles = state.cpu_cycles;
+        self.dma_stall = state.dma_stall;
+        self.oam_dma_page = None;
+        #[cfg(feature = "open-bus-decay")]
+        {
+            self.open_bus = state.open_bus.clone();
+        }
+        self.frame_complete = false;
+    }
+
+    fn device_index(&self, addr: u16) -> Option<usize> {
+        self.devices
+            .iter()
+            .position(|(range, _)| range.contains(&addr))
+    }
+
+    pub fn rom(&self) -> Option<&Rom> {
+        self.rom.as_ref()
+    }
+
+    // All PRG RAM banks, not just the one mapped at $6000
+    pub fn prg_ram(&self) -> &[u8] {
+        self.prg_ram.as_slice()
+    }
+
+    // Copies a save file into PRG RAM; this does not count as a game write
+    pub fn load_prg_ram(&mut self, data: &[u8]) {
+        self.prg_ram.load(data);
+    }
+
+    // Replaces PRG RAM with `size` bytes of cleared banks
+    pub fn set_prg_ram_size(&mut self, size: usize) {
+        self.prg_ram = PrgRam::new(size);
+    }
+
+    // Mapper hook choosing which PRG RAM bank appears at $6000-$7FFF
+    pub fn select_prg_ram_bank(&mut self, bank: usize) {
+        self.prg_ram.select_bank(bank);
+    }
+
+    // Returns whether PRG RAM was written since the last poll
+    pub fn poll_prg_ram_dirty(&mut self) -> bool {
+        std::mem::take(&mut self.prg_ram_dirty)
+    }
+
+    pub fn ppu(&self) -> Option<&PPU> {
+        self.ppu.as_ref()
+    }
+
+    pub fn ppu_mut(&mut self) -> Option<&mut PPU> {
+        self.ppu.as_mut()
+    }
+
+    pub fn apu(&self) -> &APU {
+        &self.apu
+    }
+
+    pub fn apu_mut(&mut self) -> &mut APU {
+        &mut self.apu
+    }
+
+    pub fn joypad1(&self) -> &Joypad {
+        &self.joypad1
+    }
+
+    pub fn joypad1_mut(&mut self) -> &mut Joypad {
+        &mut self.joypad1
+    }
+
+    pub fn set_region(&mut self, region: Region) {
+        self.region = region;
+        self.dot_fifths = 0;
         if let Some(ppu) = &mut self.ppu {
-            ppu.tick(count * 3);
+            ppu.set_region(region);
         }
     }
 
-    pub(crate) fn poll_nmi_status(&mut self) -> bool {
-        if let Some(ppu) = self.ppu.as_mut() {
-            if ppu.get_nmi_flag() {
-                ppu.clear_nmi_flag();
-                return true;
+    pub fn region(&self) -> Region {
+        self.region
+    }
+
+    pub fn ppu_dots(&self) -> u64 {
+        self.ppu_dots
+    }
+
+    // CPU cycles clocked through the bus since power-on, DMA stalls included
+    pub fn cpu_cycles(&self) -> u64 {
+        self.cpu_cycles
+    }
+
+    // Whether an OAM DMA is pending or still halting the CPU
+    pub fn dma_in_progress(&self) -> bool {
+        self.oam_dma_page.is_some() || self.dma_stall > 0
+    }
+
+    pub(crate) fn dma_stall(&self) -> u32 {
+        self.dma_stall
+    }
+
+    // Copies the page written to $4014 into OAM through $2004 and halts the
+    // CPU for 513 cycles, plus one to align when starting on an odd cycle
+    pub(crate) fn start_oam_dma(&mut self) {
+        let Some(page) = self.oam_dma_page.take() else {
+            return;
+        };
+        let base = (page as u16) << 8;
+        for offset in 0..256 {
+            let data = self.mem_read_u8(base + offset);
+            if let Some(ppu) = &mut self.ppu {
+                ppu.write_to_oam_data(data);
             }
         }
+        self.dma_stall = 513 + (self.cpu_cycles % 2) as u32;
+    }
+
+    // Performs one DMC sample fetch and halts the CPU for the cycles it steals:
+    // 4 on its own, but only 2 when it lands inside an OAM DMA, 1 on the
+    // second-to-last OAM DMA cycle and 3 on the last one. Returns the added
+    // stall, 0 when no sample is playing. `tick` runs it whenever the DMC's
+    // sample buffer runs empty
+    fn dmc_dma(&mut self) -> u32 {
+        let Some(addr) = self.apu.dmc_mut().next_fetch_address() else {
+            return 0;
+        };
+        let data = self.mem_read_u8(addr);
+        self.apu.dmc_mut().load_sample(data);
+        let stolen = match self.dma_stall {
+            0 => 4,
+            1 => 3,
+            2 => 1,
+            _ => 2,
+        };
+        self.dma_stall += stolen;
+        stolen
+    }
+
+    pub fn tick(&mut self, count: u32) {
+        self.cpu_cycles += count as u64;
+        self.dma_stall = self.dma_stall.saturating_sub(count);
+        self.apu.tick(count);
+        if self.apu.dmc_mut().needs_sample() {
+            self.dmc_dma();
+        }
+        // PAL's 3.2 dots per cycle leave a fraction that carries to the next tick
+        let fifths = self.dot_fifths + count * self.region.dot_fifths_per_cpu_cycle();
+        let dots = fifths / 5;
+        self.dot_fifths = fifths % 5;
+        self.ppu_dots += dots as u64;
+        #[cfg(feature = "open-bus-decay")]
+        {
+            self.open_bus.age = self.open_bus.age.saturating_add(count);
+        }
+        if let Some(ppu) = &mut self.ppu
+            && ppu.tick(dots)
+        {
+            self.frame_complete = true;
+        }
+    }
+
+    // Advances only the PPU by `dots` and reports whether an NMI is pending
+    pub fn tick_ppu(&mut self, dots: u32) -> bool {
+        match &mut self.ppu {
+            Some(ppu) => {
+                ppu.tick(dots);
+                ppu.get_nmi_flag()
+            }
+            None => false,
+        }
+    }
+
+    // Returns whether the PPU finished a frame since the last poll
+    pub fn poll_frame_complete(&mut self) -> bool {
+        std::mem::take(&mut self.frame_complete)
+    }
+
+    // CPU cycles after which an undriven bus reads back as 0
+    #[cfg(feature = "open-bus-decay")]
+    pub fn set_open_bus_decay(&mut self, cycles: u32) {
+        self.open_bus.decay_cycles = cycles;
+    }
+
+    #[cfg(feature = "open-bus-decay")]
+    fn latch_open_bus(&mut self, data: u8) {
+        self.open_bus.value = data;
+        self.open_bus.age = 0;
+    }
+
+    #[cfg(feature = "open-bus-decay")]
+    fn open_bus(&self) -> u8 {
+        if self.open_bus.age >= self.open_bus.decay_cycles {
+            0
+        } else {
+            self.open_bus.value
+        }
+    }
+
+    #[cfg(not(feature = "open-bus-decay"))]
+    fn open_bus(&self) -> u8 {
+        0
+    }
+
+    pub fn irq_pending(&self) -> bool {
+        self.apu.irq_pending()
+    }
+
+    pub(crate) fn poll_nmi_status(&mut self) -> bool {
+        if let Some(ppu) = self.ppu.as_mut()
+            && ppu.get_nmi_flag()
+        {
+            ppu.clear_nmi_flag();
+            return true;
+        }
         false
     }
 }
 
 impl Memory for Bus {
     fn mem_read_u8(&mut self, addr: u16) -> u8 {
-        match addr {
+        let data = match addr {
+            _ if let Some(index) = self.device_index(addr) => self.devices[index].1.read(addr),
             RAM_START..=RAM_END => {
                 let mirror_down_addr = addr & 0b00000111_11111111;
                 self.cpu_ram[mirror_down_addr as usize]
@@ -73,26 +427,48 @@ impl Memory for Bus {
             PPU_START..=PPU_END => self
                 .ppu
                 .as_mut()
-                .map(|ppu| match addr & 0b00100000_00000111 {
-                    0x2000 | 0x2001 | 0x2003 | 0x2005 | 0x2006 | 0x4014 => {
-                        panic!("Attempt to read from write-only PPU address {:x}", addr);
-                    }
-                    0x2007 => ppu.read_data(),
-                    _ => panic!("PPU register read not implemented for address {:x}", addr),
+                .map(|ppu| {
+                    let value = match addr & 0b00100000_00000111 {
+                        0x2000 | 0x2001 | 0x2003 | 0x2005 | 0x2006 => ppu.io_latch(),
+                        0x2002 => ppu.read_status(),
+                        0x2004 => ppu.read_oam_data(),
+                        0x2007 => ppu.read_data(),
+                        _ => panic!("PPU register read not implemented for address {:x}", addr),
+                    };
+                    ppu.latch_io(value);
+                    value
                 })
                 .unwrap_or_else(|| {
                     panic!("Attempt to read from PPU without a PPU instance");
                 }),
+            APU_STATUS => self.apu.read_status(),
+            JOYPAD_1 => self.joypad1.read(),
+            PRG_RAM_START..=PRG_RAM_END => self.prg_ram.read(addr - PRG_RAM_START),
             PRG_START..=END => self.read_prg_rom(addr),
             _ => {
                 println!("Ignoring mem access at {}", addr);
-                0
+                self.open_bus()
             }
-        }
+        };
+        let data = match self.patches.get(&addr) {
+            Some(&(value, None)) => value,
+            Some(&(value, Some(compare))) if compare == data => value,
+            _ => data,
+        };
+
+        #[cfg(feature = "open-bus-decay")]
+        self.latch_open_bus(data);
+        data
     }
 
     fn mem_write_u8(&mut self, addr: u16, data: u8) {
+        #[cfg(feature = "open-bus-decay")]
+        self.latch_open_bus(data);
+
         match addr {
+            _ if let Some(index) = self.device_index(addr) => {
+                self.devices[index].1.write(addr, data)
+            }
             RAM_START..=RAM_END => {
                 let mem_addr = addr & 0b11111111111;
                 self.cpu_ram[mem_addr as usize] = data;
@@ -100,48 +476,115 @@ impl Memory for Bus {
             PPU_START..=PPU_END => self
                 .ppu
                 .as_mut()
-                .map(|ppu| match addr & 0b00100000_00000111 {
-                    0x2000 => {
-                        ppu.write_to_ctrl(data);
-                    }
-                    0x2006 => {
-                        ppu.write_to_ppu_addr(data);
-                    }
-                    0x2007 => {
-                        ppu.write_to_data(data);
+                .map(|ppu| {
+                    #[cfg(feature = "ppu-trace")]
+                    ppu.log_register_write(addr & 0b00100000_00000111, data);
+                    ppu.latch_io(data);
+
+                    match addr & 0b00100000_00000111 {
+                        0x2000 => {
+                            ppu.write_to_ctrl(data);
+                        }
+                        0x2001 => {
+                            ppu.write_to_mask(data);
+                        }
+                        0x2003 => {
+                            ppu.write_to_oam_addr(data);
+                        }
+                        0x2004 => {
+                            ppu.write_to_oam_data(data);
+                        }
+                        0x2005 => {
+                            ppu.write_to_scroll(data);
+                        }
+                        0x2006 => {
+                            ppu.write_to_ppu_addr(data);
+                        }
+                        0x2007 => {
+                            ppu.write_to_data(data);
+                        }
+                        _ => panic!("PPU register write not implemented for address {:x}", addr),
                     }
-                    _ => panic!("PPU register write not implemented for address {:x}", addr),
                 })
                 .unwrap_or_else(|| {
                     panic!("Attempt to write to PPU without a PPU instance");
                 }),
-            PRG_START..=END => panic!("Attempt to write to Cartridge ROM space"),
+            APU_CHANNELS_START..=APU_CHANNELS_END => self.apu.write_register(addr, data),
+            DMC_START..=DMC_END => self.apu.write_dmc(addr, data),
+            OAM_DMA => self.oam_dma_page = Some(data),
+            APU_STATUS => self.apu.write_status(data),
+            JOYPAD_1 => self.joypad1.write(data),
+            FRAME_COUNTER => self.apu.write_frame_counter(data),
+            PRG_RAM_START..=PRG_RAM_END => {
+                self.prg_ram.write(addr - PRG_RAM_START, data);
+                self.prg_ram_dirty = true;
+            }
+            PRG_START..=END => {} // ROM, writes are discarded
             _ => println!("Ignoring mem write-access at {}", addr),
         }
     }
 }
 
 impl Bus {
-    fn read_prg_rom(&self, mut addr: u16) -> u8 {
-        match &self.rom {
+    // Side-effect-free read for debuggers; PPU registers are not peekable
+    pub fn peek_u8(&self, addr: u16) -> u8 {
+        match addr {
+            RAM_START..=RAM_END => self.cpu_ram[(addr & 0b00000111_11111111) as usize],
+            PRG_RAM_START..=PRG_RAM_END => self.prg_ram.read(addr - PRG_RAM_START),
+            PRG_START..=END if self.rom.is_some() => self.read_prg_rom(addr),
+            _ => 0,
+        }
+    }
+
+    // Counterpart of peek_u8: stores straight into RAM, PRG RAM or PRG ROM.
+    // Memory-mapped registers have no backing store and are left alone
+    pub fn poke_u8(&mut self, addr: u16, data: u8) {
+        match addr {
+            RAM_START..=RAM_END => self.cpu_ram[(addr & 0b00000111_11111111) as usize] = data,
+            PRG_RAM_START..=PRG_RAM_END => self.prg_ram.write(addr - PRG_RAM_START, data),
+            PRG_START..=END if self.rom.is_some() => self.write_prg_rom(addr, data),
+            _ => {}
+        }
+    }
+
+    // Loader hook that bypasses the write protection of the PRG ROM
+    pub fn write_prg_rom(&mut self, addr: u16, data: u8) {
+        match &mut self.rom {
             Some(rom) => {
-                addr -= 0x8000;
-                if rom.prg_rom.len() == 0x4000 && addr >= 0x4000 {
-                    addr %= 0x4000;
-                }
-                rom.prg_rom[addr as usize]
+                let index = Self::prg_rom_index(rom, addr);
+                rom.prg_rom[index] = data;
+            }
+            None => {
+                panic!("Trying to write ROM without a cartridge")
             }
+        }
+    }
+
+    fn read_prg_rom(&self, addr: u16) -> u8 {
+        match &self.rom {
+            Some(rom) => rom.prg_rom[Self::prg_rom_index(rom, addr)],
             None => {
                 panic!("Trying to read ROM without a cartridge")
             }
         }
     }
+
+    fn prg_rom_index(rom: &Rom, mut addr: u16) -> usize {
+        addr -= 0x8000;
+        // NROM-128: the single 16KB bank is mirrored into 0xC000-0xFFFF
+        if rom.prg_rom.len() == 0x4000 && addr >= 0x4000 {
+            addr %= 0x4000;
+        }
+        addr as usize
+    }
 }
 
 #[cfg(test)]
 mod bus_tests {
     use super::super::bus::Bus;
-    use super::super::{Memory, rom::Rom};
+    use super::super::{BusDevice, Memory, rom::Rom};
+    use crate::joypad::JoypadButton;
+    use crate::region::Region;
 
     #[test]
     fn test_bus_new() {
@@ -200,6 +643,51 @@ mod bus_tests {
         assert_eq!(bus.mem_read_u8(0x0001), 0x12); // High byte
     }
 
+    #[test]
+    fn test_bus_16_bit_read_across_regions() {
+        let mut prg_rom = vec![0; 32 * 1024];
+        prg_rom[0x0000] = 0x12; // 0x8000
+        prg_rom[0x7FFF] = 0x34; // 0xFFFF
+        let mut bus = Bus::from_rom(Rom::from_prg(&prg_rom));
+        bus.mem_write_u8(0x0000, 0x56);
+
+        // Low byte from PRG RAM, high byte from PRG ROM
+        assert_eq!(bus.mem_read_u16(0x7FFF), 0x1200);
+
+        // Low byte from PRG ROM, high byte wraps around into RAM
+        assert_eq!(bus.mem_read_u16(0xFFFF), 0x5634);
+    }
+
+    #[test]
+    fn test_bus_16_bit_write_across_ram_mirror() {
+        let mut bus = Bus::new();
+
+        bus.mem_write_u16(0x07FF, 0xABCD);
+        assert_eq!(bus.mem_read_u8(0x07FF), 0xCD);
+        assert_eq!(bus.mem_read_u8(0x0000), 0xAB); // 0x0800 mirrors 0x0000
+    }
+
+    #[test]
+    fn test_bus_16_bit_write_across_ram_and_ppu() {
+        let mut bus = Bus::from_rom(Rom::from_pc(0x8000));
+
+        // Low byte lands in the last RAM mirror, high byte in PPUCTRL
+        bus.mem_write_u16(0x1FFF, 0x8012);
+        assert_eq!(bus.mem_read_u8(0x07FF), 0x12);
+        assert_eq!(bus.ppu().unwrap().ctrl_bits(), 0x80);
+    }
+
+    #[test]
+    fn test_bus_16_bit_read_across_ram_and_ppu() {
+        let mut bus = Bus::from_rom(Rom::from_pc(0x8000));
+        bus.mem_write_u8(0x07FF, 0x12);
+        bus.mem_write_u8(0x2001, 0x34); // Leaves 0x34 on the PPU data bus
+
+        // Low byte from the last RAM mirror, high byte is open bus from the
+        // write-only PPUCTRL
+        assert_eq!(bus.mem_read_u16(0x1FFF), 0x3412);
+    }
+
     #[test]
     fn test_bus_from_rom() {
         // Create a test ROM
@@ -228,6 +716,19 @@ mod bus_tests {
         assert_eq!(prg_data, 0xAA);
     }
 
+    #[test]
+    fn test_bus_insert_rom_clears_prg_ram() {
+        let mut bus = Bus::new();
+        bus.insert_rom(Rom::new(&create_test_rom_data()).unwrap());
+        bus.mem_write_u8(0x6000, 0x42);
+        bus.mem_write_u8(0x7FFF, 0x24);
+
+        // Swapping in a cartridge with the same PRG RAM size
+        bus.insert_rom(Rom::new(&create_test_rom_data()).unwrap());
+        assert_eq!(bus.mem_read_u8(0x6000), 0);
+        assert_eq!(bus.mem_read_u8(0x7FFF), 0);
+    }
+
     #[test]
     fn test_bus_rom_read_operations() {
         let mut bus = Bus::new();
@@ -243,7 +744,21 @@ mod bus_tests {
     }
 
     #[test]
-    #[should_panic(expected = "Attempt to write to Cartridge ROM space")]
+    fn test_bus_nrom_128_mirroring() {
+        let mut prg_rom = vec![0; 16 * 1024];
+        prg_rom[0] = 0x5A; // Sentinel at the start of the only bank
+        prg_rom[0x3FFF] = 0xA5; // Sentinel at the end of the only bank
+
+        let mut bus = Bus::from_rom(Rom::from_prg(&prg_rom));
+
+        // A single 16KB bank is visible at both 0x8000 and 0xC000
+        assert_eq!(bus.mem_read_u8(0x8000), 0x5A);
+        assert_eq!(bus.mem_read_u8(0xC000), 0x5A);
+        assert_eq!(bus.mem_read_u8(0xBFFF), 0xA5);
+        assert_eq!(bus.mem_read_u8(0xFFFF), 0xA5);
+    }
+
+    #[test]
     fn test_bus_rom_write_protection() {
         let mut bus = Bus::new();
 
@@ -251,8 +766,17 @@ mod bus_tests {
         let rom = Rom::new(&rom_data).unwrap();
         bus.insert_rom(rom);
 
-        // Writing to ROM space should panic
+        // Writing to ROM space is ignored like on hardware
         bus.mem_write_u8(0x8000, 0x42);
+        assert_eq!(bus.mem_read_u8(0x8000), 0xAA);
+    }
+
+    #[test]
+    fn test_bus_write_prg_rom_seeds_rom() {
+        let mut bus = Bus::from_rom(Rom::from_prg(&vec![0; 16 * 1024]));
+
+        bus.write_prg_rom(0xC001, 0x42); // Mirror of 0x8001
+        assert_eq!(bus.mem_read_u8(0x8001), 0x42);
     }
 
     #[test]
@@ -278,6 +802,289 @@ mod bus_tests {
         // but we can test that it doesn't panic
     }
 
+    #[test]
+    fn test_bus_ppu_ctrl_and_mask_writes() {
+        let mut bus = Bus::from_rom(Rom::from_pc(0x8000));
+
+        bus.mem_write_u8(0x2000, 0b1001_0000);
+        bus.mem_write_u8(0x2009, 0b0000_1010); // Mirror of $2001
+
+        let ppu = bus.ppu().unwrap();
+        assert_eq!(ppu.ctrl_bits(), 0b1001_0000);
+        assert_eq!(ppu.mask_bits(), 0b0000_1010);
+    }
+
+    #[test]
+    fn test_bus_chr_ram_is_writable() {
+        let rom = Rom::new(&Rom::create_rom_data(1, 0, 0x00, 0x00, false)).unwrap();
+        let mut bus = Bus::from_rom(rom);
+
+        bus.mem_write_u8(0x2006, 0x1F);
+        bus.mem_write_u8(0x2006, 0xFF);
+        bus.mem_write_u8(0x2007, 0x5A);
+
+        let ppu = bus.ppu().unwrap();
+        assert_eq!(ppu.chr_rom.len(), 0x2000);
+        assert_eq!(ppu.chr_rom[0x1FFF], 0x5A);
+    }
+
+    #[test]
+    fn test_bus_oam_registers() {
+        let mut bus = Bus::from_rom(Rom::from_pc(0x8000));
+
+        bus.mem_write_u8(0x2003, 0x10);
+        bus.mem_write_u8(0x2004, 0xAB);
+        bus.mem_write_u8(0x2003, 0x10);
+
+        assert_eq!(bus.mem_read_u8(0x2004), 0xAB);
+        assert_eq!(crate::ppu::debug::oam(bus.ppu().unwrap())[0x10], 0xAB);
+    }
+
+    #[test]
+    fn test_bus_ppu_accessors() {
+        let mut bus = Bus::new();
+        assert!(bus.ppu().is_none());
+        assert!(bus.ppu_mut().is_none());
+
+        let rom_data = create_test_rom_data();
+        bus.insert_rom(Rom::new(&rom_data).unwrap());
+
+        assert!(bus.ppu().is_some());
+        assert!(bus.ppu_mut().is_some());
+    }
+
+    #[test]
+    #[cfg(feature = "ppu-trace")]
+    fn test_bus_ppu_write_log() {
+        let rom_data = create_test_rom_data();
+        let mut bus = Bus::from_rom(Rom::new(&rom_data).unwrap());
+
+        bus.tick_ppu(5);
+        bus.mem_write_u8(0x2000, 0x80);
+        bus.tick_ppu(341 * 2 + 10);
+        bus.mem_write_u8(0x3FFE, 0x21); // Mirror of $2006
+
+        let log = bus.ppu_mut().unwrap().take_ppu_write_log();
+        assert_eq!(log, vec![(0, 5, 0x2000, 0x80), (2, 15, 0x2006, 0x21)]);
+
+        // Taking the log drains it
+        assert!(bus.ppu_mut().unwrap().take_ppu_write_log().is_empty());
+    }
+
+    #[test]
+    fn test_bus_prg_ram() {
+        let mut bus = Bus::new();
+
+        bus.mem_write_u8(0x6000, 0x12);
+        bus.mem_write_u8(0x7FFF, 0x34);
+        assert_eq!(bus.mem_read_u8(0x6000), 0x12);
+        assert_eq!(bus.peek_u8(0x7FFF), 0x34);
+        assert_eq!(bus.prg_ram()[0x1FFF], 0x34);
+    }
+
+    #[test]
+    fn test_bus_prg_ram_banks() {
+        let mut bus = Bus::new();
+        bus.set_prg_ram_size(0x8000);
+
+        bus.mem_write_u8(0x6000, 0x11);
+        bus.select_prg_ram_bank(1);
+        assert_eq!(bus.mem_read_u8(0x6000), 0x00);
+        bus.mem_write_u8(0x6000, 0x22);
+
+        bus.select_prg_ram_bank(0);
+        assert_eq!(bus.mem_read_u8(0x6000), 0x11);
+        bus.select_prg_ram_bank(1);
+        assert_eq!(bus.peek_u8(0x6000), 0x22);
+
+        // Save files hold every bank
+        assert_eq!(bus.prg_ram().len(), 0x8000);
+        assert_eq!(bus.prg_ram()[0x2000], 0x22);
+    }
+
+    struct ScratchDevice {
+        data: [u8; 0x1000],
+    }
+
+    impl BusDevice for ScratchDevice {
+        fn read(&mut self, addr: u16) -> u8 {
+            self.data[(addr - 0x4000) as usize]
+        }
+
+        fn write(&mut self, addr: u16, data: u8) {
+            self.data[(addr - 0x4000) as usize] = data;
+        }
+    }
+
+    #[test]
+    fn test_bus_register_device() {
+        let mut bus = Bus::new();
+        bus.register_device(
+            0x4020..=0x4FFF,
+            Box::new(ScratchDevice { data: [0; 0x1000] }),
+        );
+
+        bus.mem_write_u8(0x4020, 0x12);
+        bus.mem_write_u8(0x4FFF, 0x34);
+        assert_eq!(bus.mem_read_u8(0x4020), 0x12);
+        assert_eq!(bus.mem_read_u8(0x4FFF), 0x34);
+
+        // Built-in RAM is untouched
+        assert!((0..0x800).all(|addr| bus.mem_read_u8(addr) == 0));
+    }
+
+    #[test]
+    fn test_bus_prg_ram_dirty() {
+        let mut bus = Bus::new();
+
+        bus.load_prg_ram(&[0x11, 0x22]);
+        assert_eq!(bus.mem_read_u8(0x6001), 0x22);
+        assert!(!bus.poll_prg_ram_dirty());
+
+        bus.mem_write_u8(0x6000, 0x33);
+        assert!(bus.poll_prg_ram_dirty());
+        assert!(!bus.poll_prg_ram_dirty());
+    }
+
+    #[test]
+    fn test_bus_apu_status_clears_frame_irq() {
+        let mut bus = Bus::new();
+        bus.tick(29830);
+        assert!(bus.irq_pending());
+
+        assert_eq!(bus.mem_read_u8(0x4015) & 0b0100_0000, 0b0100_0000);
+        assert!(!bus.irq_pending());
+
+        // Inhibiting through $4017 keeps it from coming back
+        bus.mem_write_u8(0x4017, 0b0100_0000);
+        bus.tick(29830);
+        assert!(!bus.irq_pending());
+    }
+
+    #[test]
+    fn test_bus_joypad_port() {
+        let mut bus = Bus::new();
+        bus.joypad1_mut().set_buttons(JoypadButton::BUTTON_B);
+
+        bus.mem_write_u8(0x4016, 1);
+        bus.mem_write_u8(0x4016, 0);
+        assert_eq!(bus.mem_read_u8(0x4016), 0); // A
+        assert_eq!(bus.mem_read_u8(0x4016), 1); // B
+    }
+
+    #[cfg(not(feature = "open-bus-decay"))]
+    #[test]
+    fn test_bus_unmapped_read_without_decay_model() {
+        let mut bus = Bus::new();
+        bus.mem_write_u8(0x0000, 0x42);
+        bus.mem_read_u8(0x0000);
+
+        assert_eq!(bus.mem_read_u8(0x5000), 0);
+    }
+
+    #[cfg(feature = "open-bus-decay")]
+    #[test]
+    fn test_bus_open_bus_decay() {
+        let mut bus = Bus::new();
+        bus.set_open_bus_decay(1000);
+        bus.mem_write_u8(0x0000, 0x42);
+        bus.mem_read_u8(0x0000);
+
+        // Unmapped reads see the last value on the bus
+        bus.tick(999);
+        assert_eq!(bus.mem_read_u8(0x5000), 0x42);
+
+        // ...and the read itself re-drives the bus
+        bus.tick(999);
+        assert_eq!(bus.mem_read_u8(0x5000), 0x42);
+
+        bus.tick(1000);
+        assert_eq!(bus.mem_read_u8(0x5000), 0x00);
+    }
+
+    #[test]
+    fn test_bus_peek_u8() {
+        let mut bus = Bus::new();
+        bus.mem_write_u8(0x0012, 0x34);
+
+        assert_eq!(bus.peek_u8(0x0012), 0x34);
+        assert_eq!(bus.peek_u8(0x0812), 0x34); // RAM mirror
+        assert_eq!(bus.peek_u8(0x8000), 0); // No cartridge, no panic
+
+        bus.insert_rom(Rom::new(&create_test_rom_data()).unwrap());
+        assert_eq!(bus.peek_u8(0x8000), 0xAA);
+        assert_eq!(bus.peek_u8(0x2002), 0); // PPU registers are not peeked
+    }
+
+    #[test]
+    fn test_ppu_status_low_bits_are_open_bus() {
+        let mut bus = Bus::from_rom(Rom::new(&create_test_rom_data()).unwrap());
+        bus.mem_write_u8(0x2003, 0x1F); // Any register write sets the latch
+
+        assert_eq!(bus.mem_read_u8(0x2002) & 0b0001_1111, 0x1F);
+
+        bus.mem_write_u8(0x2005, 0xE2);
+        assert_eq!(bus.mem_read_u8(0x2002) & 0b0001_1111, 0x02);
+    }
+
+    #[test]
+    fn test_status_read_resets_latch_between_scroll_and_addr() {
+        let mut bus = Bus::from_rom(Rom::new(&create_test_rom_data()).unwrap());
+
+        bus.mem_write_u8(0x2005, 0x7D); // First scroll write leaves w set
+        bus.mem_read_u8(0x2002); // Resets w
+        bus.mem_write_u8(0x2006, 0x21); // Taken as the high byte
+        bus.mem_write_u8(0x2006, 0x08);
+        assert_eq!(bus.ppu().unwrap().vram_addr(), 0x2108);
+
+        bus.mem_write_u8(0x2007, 0x55);
+        bus.mem_write_u8(0x2006, 0x21);
+        bus.mem_write_u8(0x2006, 0x08);
+        bus.mem_read_u8(0x2007); // Buffered read
+        assert_eq!(bus.mem_read_u8(0x2007), 0x55);
+    }
+
+    #[test]
+    fn test_pal_accumulates_fractional_dots() {
+        let mut bus = Bus::from_rom(Rom::new(&create_test_rom_data()).unwrap());
+        bus.set_region(Region::Pal);
+
+        bus.tick(7);
+        assert_eq!(bus.ppu_dots(), 22); // 22.4, the 0.4 is carried
+
+        for _ in 0..993 {
+            bus.tick(1);
+        }
+        assert_eq!(bus.ppu_dots(), 1000 * 16 / 5);
+
+        bus.set_region(Region::Ntsc);
+        bus.tick(10);
+        assert_eq!(bus.ppu_dots(), 3200 + 30);
+    }
+
+    #[test]
+    fn test_patch_overrides_reads_only() {
+        let mut bus = Bus::from_rom(Rom::new(&create_test_rom_data()).unwrap());
+        bus.patch(0x8000, 0x42);
+
+        assert_eq!(bus.mem_read_u8(0x8000), 0x42);
+        assert_eq!(bus.mem_read_u8(0x8001), 0xAA);
+        assert_eq!(bus.peek_u8(0x8000), 0xAA); // ROM is unchanged
+
+        bus.clear_patch(0x8000);
+        assert_eq!(bus.mem_read_u8(0x8000), 0xAA);
+    }
+
+    #[test]
+    fn test_patch_compare() {
+        let mut bus = Bus::from_rom(Rom::new(&create_test_rom_data()).unwrap());
+        bus.patch_compare(0x8000, 0x42, 0xAA);
+        bus.patch_compare(0x8001, 0x42, 0x00);
+
+        assert_eq!(bus.mem_read_u8(0x8000), 0x42); // Real byte matches
+        assert_eq!(bus.mem_read_u8(0x8001), 0xAA); // Real byte differs
+    }
+
     // Helper function to create test ROM data
     fn create_test_rom_data() -> Vec<u8> {
         let mut rom_data = Vec::new();
@@ -298,4 +1105,101 @@ mod bus_tests {
 
         rom_data
     }
+
+    fn bus_with_dmc_sample() -> Bus {
+        let mut bus = Bus::from_rom(Rom::from_pc(0x8000));
+        bus.mem_write_u8(0x4013, 1); // 17 byte sample
+        bus.mem_write_u8(0x4015, 0b0001_0000);
+        bus
+    }
+
+    #[test]
+    fn test_bus_dmc_dma_alone() {
+        let mut bus = bus_with_dmc_sample();
+
+        bus.tick(1); // The empty sample buffer triggers a fetch
+        assert!(bus.dma_in_progress());
+        assert_eq!(bus.dma_stall(), 4);
+        assert_eq!(bus.apu().dmc().bytes_remaining(), 16);
+    }
+
+    #[test]
+    fn test_bus_dmc_dma_refills_buffer() {
+        let mut bus = bus_with_dmc_sample();
+        bus.mem_write_u8(0x4010, 0x0F); // 54 cycles per bit
+
+        // One byte up front, then another every 8 bits. The first bit still
+        // takes the power-on period of 428 cycles
+        bus.tick(1);
+        for _ in 0..428 + 15 * 54 {
+            bus.tick(1);
+        }
+        assert_eq!(bus.apu().dmc().bytes_remaining(), 17 - 3);
+    }
+
+    #[test]
+    fn test_bus_dmc_dma_during_oam_dma() {
+        let mut bus = bus_with_dmc_sample();
+        bus.mem_write_u8(0x4014, 0x02);
+        bus.start_oam_dma();
+
+        // The DMC fetch only adds 2 cycles on top of the 513 OAM DMA cycles
+        bus.tick(100);
+        assert_eq!(bus.dma_stall(), 415);
+    }
+
+    // Ticks one cycle at a time until the running DMA lets go of the CPU
+    fn stalled_cycles(bus: &mut Bus) -> u32 {
+        let mut cycles = 0;
+        while bus.dma_in_progress() {
+            bus.tick(1);
+            cycles += 1;
+        }
+        cycles
+    }
+
+    #[test]
+    fn test_bus_dmc_dma_on_last_oam_dma_cycle() {
+        let mut bus = bus_with_dmc_sample();
+        bus.mem_write_u8(0x4010, 0x0F); // 54 cycles per bit
+
+        // The first byte is fetched right away and leaves the buffer after
+        // 8 bits, on cycle 428 + 7 * 54 = 806
+        for _ in 0..294 {
+            bus.tick(1);
+        }
+        assert_eq!(bus.apu().dmc().bytes_remaining(), 16);
+
+        // Starting on even cycle 294, the 513 OAM DMA cycles end on 806
+        bus.mem_write_u8(0x4014, 0x02);
+        bus.start_oam_dma();
+        assert_eq!(stalled_cycles(&mut bus), 513 + 3);
+        assert_eq!(bus.apu().dmc().bytes_remaining(), 15);
+    }
+
+    #[test]
+    fn test_bus_dmc_dma_on_second_to_last_oam_dma_cycle() {
+        let mut bus = Bus::from_rom(Rom::from_pc(0x8000));
+        bus.mem_write_u8(0x4013, 1); // 17 byte sample
+        bus.mem_write_u8(0x4014, 0x02);
+        bus.start_oam_dma();
+
+        // The output unit empties the buffer on even cycles only, where an
+        // OAM DMA always has an odd number of cycles left. Enabling the
+        // channel with an empty buffer fetches on the next cycle instead
+        for _ in 0..510 {
+            bus.tick(1);
+        }
+        bus.mem_write_u8(0x4015, 0b0001_0000);
+        assert_eq!(510 + stalled_cycles(&mut bus), 513 + 1);
+        assert_eq!(bus.apu().dmc().bytes_remaining(), 16);
+    }
+
+    #[test]
+    fn test_bus_dmc_dma_without_sample() {
+        let mut bus = Bus::from_rom(Rom::from_pc(0x8000));
+        bus.tick(1000);
+        assert_eq!(bus.dmc_dma(), 0);
+        assert!(!bus.dma_in_progress());
+    }
 }
@@ -0,0 +1,67 @@
+const ALPHABET: &[u8; 16] = b"APZLGITYEOXUKSVN";
+
+// Decodes a 6- or 8-letter Game Genie code into (address, value, compare)
+pub fn decode_game_genie(code: &str) -> Result<(u16, u8, Option<u8>), String> {
+    let n = code
+        .bytes()
+        .map(|letter| {
+            ALPHABET
+                .iter()
+                .position(|&c| c == letter.to_ascii_uppercase())
+                .map(|index| index as u16)
+                .ok_or_else(|| format!("Invalid Game Genie letter '{}'", letter as char))
+        })
+        .collect::<Result<Vec<u16>, String>>()?;
+
+    if n.len() != 6 && n.len() != 8 {
+        return Err(format!(
+            "Game Genie codes have 6 or 8 letters, got {}",
+            n.len()
+        ));
+    }
+
+    let address = 0x8000
+        | ((n[3] & 7) << 12)
+        | ((n[5] & 7) << 8)
+        | ((n[4] & 8) << 8)
+        | ((n[2] & 7) << 4)
+        | ((n[1] & 8) << 4)
+        | (n[4] & 7)
+        | (n[3] & 8);
+
+    // The high bit of the value lives in the last letter
+    let value_bit3 = if n.len() == 6 { n[5] & 8 } else { n[7] & 8 };
+    let value = ((n[1] & 7) << 4) | ((n[0] & 8) << 4) | (n[0] & 7) | value_bit3;
+
+    let compare = (n.len() == 8)
+        .then(|| ((n[7] & 7) << 4) | ((n[6] & 8) << 4) | (n[6] & 7) | (n[5] & 8))
+        .map(|compare| compare as u8);
+
+    Ok((address, value as u8, compare))
+}
+
+#[cfg(test)]
+mod game_genie_tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_six_letter_code() {
+        // Super Mario Bros. infinite lives
+        assert_eq!(decode_game_genie("SXIOPO"), Ok((0x91D9, 0xAD, None)));
+        assert_eq!(decode_game_genie("sxiopo"), Ok((0x91D9, 0xAD, None)));
+    }
+
+    #[test]
+    fn test_decode_eight_letter_code() {
+        let (address, value, compare) = decode_game_genie("SXIOPOAP").unwrap();
+        assert_eq!(address, 0x91D9);
+        assert_eq!(value, 0xA5); // Bit 3 now comes from the last letter
+        assert_eq!(compare, Some(0x18));
+    }
+
+    #[test]
+    fn test_decode_rejects_bad_codes() {
+        assert!(decode_game_genie("SXIOP").is_err());
+        assert!(decode_game_genie("SXIOPB").is_err());
+    }
+}
@@ -0,0 +1,122 @@
+// A12 must have been low this many PPU cycles for a rise to clock the counter,
+// which filters out the quick toggles between background and sprite fetches
+const A12_LOW_FILTER_CYCLES: u64 = 8;
+
+// MMC3 scanline counter, clocked by rising edges of PPU address line A12.
+// Not wired into the bus yet since only NROM is supported
+#[derive(Clone, Default)]
+pub struct Mmc3Irq {
+    latch: u8,
+    counter: u8,
+    reload: bool,
+    enabled: bool,
+    pending: bool,
+    a12: bool,
+    a12_fell_at: u64,
+}
+
+impl Mmc3Irq {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // $C000-$FFFF, decoded by the address range and whether it is even or odd
+    pub fn write_register(&mut self, addr: u16, data: u8) {
+        match (addr, addr & 1) {
+            (0xC000..=0xDFFF, 0) => self.latch = data,
+            (0xC000..=0xDFFF, _) => {
+                self.counter = 0;
+                self.reload = true;
+            }
+            (0xE000..=0xFFFF, 0) => {
+                self.enabled = false;
+                self.pending = false; // Disabling also acknowledges
+            }
+            (0xE000..=0xFFFF, _) => self.enabled = true,
+            _ => {}
+        }
+    }
+
+    // Reports the level of A12 for a PPU access at `ppu_cycle`
+    pub fn observe_a12(&mut self, a12: bool, ppu_cycle: u64) {
+        if a12 && !self.a12 && ppu_cycle.saturating_sub(self.a12_fell_at) >= A12_LOW_FILTER_CYCLES {
+            self.clock_counter();
+        }
+        if !a12 && self.a12 {
+            self.a12_fell_at = ppu_cycle;
+        }
+        self.a12 = a12;
+    }
+
+    fn clock_counter(&mut self) {
+        if self.counter == 0 || self.reload {
+            self.counter = self.latch;
+            self.reload = false;
+        } else {
+            self.counter -= 1;
+        }
+        if self.counter == 0 && self.enabled {
+            self.pending = true;
+        }
+    }
+
+    pub fn counter(&self) -> u8 {
+        self.counter
+    }
+
+    pub fn irq_pending(&self) -> bool {
+        self.pending
+    }
+}
+
+#[cfg(test)]
+mod mmc3_tests {
+    use super::*;
+
+    // One rise per scanline: low for the background fetches, high at dot 260
+    fn clock_scanline(irq: &mut Mmc3Irq, scanline: u64) {
+        let start = scanline * 341;
+        irq.observe_a12(false, start);
+        irq.observe_a12(true, start + 260);
+    }
+
+    #[test]
+    fn test_counter_ignores_close_rises() {
+        let mut irq = Mmc3Irq::new();
+        irq.write_register(0xC000, 5);
+        irq.write_register(0xC001, 0);
+
+        clock_scanline(&mut irq, 0);
+        assert_eq!(irq.counter(), 5); // Reloaded from the latch
+
+        clock_scanline(&mut irq, 1);
+        assert_eq!(irq.counter(), 4);
+
+        // A12 dips for a few cycles and comes back up: filtered out
+        irq.observe_a12(false, 341 + 300);
+        irq.observe_a12(true, 341 + 304);
+        assert_eq!(irq.counter(), 4);
+
+        // Low for long enough counts again
+        irq.observe_a12(false, 341 + 320);
+        irq.observe_a12(true, 341 + 328);
+        assert_eq!(irq.counter(), 3);
+    }
+
+    #[test]
+    fn test_irq_fires_when_counter_reaches_zero() {
+        let mut irq = Mmc3Irq::new();
+        irq.write_register(0xC000, 2);
+        irq.write_register(0xE001, 0);
+
+        for scanline in 0..3 {
+            assert!(!irq.irq_pending());
+            clock_scanline(&mut irq, scanline);
+        }
+        assert_eq!(irq.counter(), 0);
+        assert!(irq.irq_pending());
+
+        irq.write_register(0xE000, 0);
+        assert!(!irq.irq_pending());
+    }
+}
@@ -1,5 +1,8 @@
 pub mod bus;
+pub mod game_genie;
 pub mod memory;
+pub mod mmc3;
+pub mod prg_ram;
 pub mod rom;
 
 pub trait Memory {
@@ -9,7 +12,7 @@ pub trait Memory {
 
     fn mem_read_u16(&mut self, addr: u16) -> u16 {
         let lo = self.mem_read_u8(addr) as u16;
-        let hi = self.mem_read_u8(addr + 1) as u16;
+        let hi = self.mem_read_u8(addr.wrapping_add(1)) as u16;
         (hi << 8) | lo
     }
 
@@ -17,6 +20,13 @@ pub trait Memory {
         let lo = data as u8;
         let hi = (data >> 8) as u8;
         self.mem_write_u8(addr, lo);
-        self.mem_write_u8(addr + 1, hi);
+        self.mem_write_u8(addr.wrapping_add(1), hi);
     }
 }
+
+// Custom hardware mapped onto the CPU bus; receives the full CPU address
+pub trait BusDevice {
+    fn read(&mut self, addr: u16) -> u8;
+
+    fn write(&mut self, addr: u16, data: u8);
+}
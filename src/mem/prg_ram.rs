@@ -0,0 +1,98 @@
+// Size of the $6000-$7FFF window
+pub const PRG_RAM_BANK_SIZE: usize = 0x2000;
+
+// Cartridge PRG RAM. Carts with more than 8KB expose one bank at a time
+// through $6000-$7FFF and the mapper picks which
+#[derive(Clone)]
+pub struct PrgRam {
+    data: Vec<u8>,
+    bank: usize,
+}
+
+impl PrgRam {
+    // Sizes are rounded up to whole banks, with at least one bank
+    pub fn new(size: usize) -> Self {
+        let banks = size.div_ceil(PRG_RAM_BANK_SIZE).max(1);
+        PrgRam {
+            data: vec![0; banks * PRG_RAM_BANK_SIZE],
+            bank: 0,
+        }
+    }
+
+    pub fn bank_count(&self) -> usize {
+        self.data.len() / PRG_RAM_BANK_SIZE
+    }
+
+    // Out of range banks wrap, like the unused upper bits of a bank register
+    pub fn select_bank(&mut self, bank: usize) {
+        self.bank = bank % self.bank_count();
+    }
+
+    pub fn selected_bank(&self) -> usize {
+        self.bank
+    }
+
+    // `offset` is relative to $6000
+    pub fn read(&self, offset: u16) -> u8 {
+        self.data[self.index(offset)]
+    }
+
+    pub fn write(&mut self, offset: u16, data: u8) {
+        let index = self.index(offset);
+        self.data[index] = data;
+    }
+
+    // Every bank back to back, as stored in a save file
+    pub fn as_slice(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn load(&mut self, data: &[u8]) {
+        let len = data.len().min(self.data.len());
+        self.data[..len].copy_from_slice(&data[..len]);
+    }
+
+    fn index(&self, offset: u16) -> usize {
+        self.bank * PRG_RAM_BANK_SIZE + (offset as usize % PRG_RAM_BANK_SIZE)
+    }
+}
+
+impl Default for PrgRam {
+    fn default() -> Self {
+        PrgRam::new(PRG_RAM_BANK_SIZE)
+    }
+}
+
+#[cfg(test)]
+mod prg_ram_tests {
+    use super::*;
+
+    #[test]
+    fn test_size_rounds_up_to_banks() {
+        assert_eq!(PrgRam::new(0).bank_count(), 1);
+        assert_eq!(PrgRam::new(0x2000).bank_count(), 1);
+        assert_eq!(PrgRam::new(0x2001).bank_count(), 2);
+        assert_eq!(PrgRam::new(0x8000).as_slice().len(), 0x8000);
+    }
+
+    #[test]
+    fn test_banks_are_independent() {
+        let mut ram = PrgRam::new(0x8000);
+
+        ram.write(0x0000, 0x11);
+        ram.select_bank(2);
+        assert_eq!(ram.read(0x0000), 0x00);
+        ram.write(0x0000, 0x22);
+
+        ram.select_bank(0);
+        assert_eq!(ram.read(0x0000), 0x11);
+        assert_eq!(ram.as_slice()[0x4000], 0x22);
+    }
+
+    #[test]
+    fn test_select_bank_wraps() {
+        let mut ram = PrgRam::new(0x4000);
+        ram.select_bank(3);
+        assert_eq!(ram.selected_bank(), 1);
+    }
+}
@@ -1,63 +1,171 @@
+use std::fmt;
+
+use crate::utils::fnv1a;
+
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Mirroring {
     Vertical,
     Horizontal,
     FourScreen,
+    SingleScreenA,
+    SingleScreenB,
+}
+
+impl Mirroring {
+    // Maps the four-screen and vertical mirroring bits of the iNES header
+    pub fn from_control_bits(four_screen: bool, vertical: bool) -> Mirroring {
+        match (four_screen, vertical) {
+            (true, _) => Mirroring::FourScreen,
+            (false, true) => Mirroring::Vertical,
+            (false, false) => Mirroring::Horizontal,
+        }
+    }
+}
+
+impl fmt::Display for Mirroring {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mirroring::Vertical => write!(f, "Vertical"),
+            Mirroring::Horizontal => write!(f, "Horizontal"),
+            Mirroring::FourScreen => write!(f, "Four-screen"),
+            Mirroring::SingleScreenA => write!(f, "Single-screen A"),
+            Mirroring::SingleScreenB => write!(f, "Single-screen B"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum RomError {
+    InvalidTag,
+    UnsupportedVersion,
+    UnsupportedMapper(u8),
+    Io(std::io::ErrorKind),
+}
+
+impl fmt::Display for RomError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RomError::InvalidTag => write!(f, "File is not in iNES file format"),
+            RomError::UnsupportedVersion => write!(f, "Only iNES 1.0 file format is supported"),
+            RomError::UnsupportedMapper(mapper) => write!(f, "Mapper {} is not supported", mapper),
+            RomError::Io(kind) => write!(f, "Could not read ROM file: {}", kind),
+        }
+    }
 }
 
+impl std::error::Error for RomError {}
+
 const NES_TAG: [u8; 4] = [0x4E, 0x45, 0x53, 0x1A];
 const PRG_ROM_PAGE_SIZE: usize = 16384;
 const CHR_ROM_PAGE_SIZE: usize = 8192;
+const PRG_RAM_PAGE_SIZE: usize = 8192;
+const TRAINER_SIZE: usize = 512;
+
+// Identifies a cartridge by its PRG and CHR contents, e.g. to key cached save
+// states or settings. Stable across runs and builds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RomId(pub u64);
 
 #[derive(Debug, Clone)]
 pub struct Rom {
     pub prg_rom: Vec<u8>,
     pub chr_rom: Vec<u8>,
+    pub chr_is_ram: bool, // No CHR ROM in the image, the board has 8KB of CHR RAM
     pub mapper: u8,
     pub screen_mirroring: Mirroring,
+    pub has_battery: bool,
+    pub trainer: Option<[u8; TRAINER_SIZE]>,
+    pub prg_ram_size: usize, // Battery/work RAM at $6000-$7FFF, in bytes
 }
 
 impl Rom {
-    pub fn new(raw: &[u8]) -> Result<Rom, String> {
+    pub fn new(raw: &[u8]) -> Result<Rom, RomError> {
         if raw[0..4] != NES_TAG {
-            return Err("File is not in iNES file format".to_string());
+            return Err(RomError::InvalidTag);
         }
 
         let control_byte_1 = raw[6];
         let control_byte_2 = raw[7];
 
         if control_byte_1 & 0b0000_1100 != 0 {
-            return Err("Only iNES 1.0 file format is supported".to_string());
+            return Err(RomError::UnsupportedVersion);
         }
 
         let vertical_mirroring_flag = control_byte_2 & 0b0000_0001 != 0;
-        #[allow(unused_variables)]
         let battery_ram_flag = control_byte_2 & 0b0000_0010 != 0;
         let trainer_flag = control_byte_2 & 0b0000_0100 != 0;
         let four_screen_flag = control_byte_2 & 0b0000_1000 != 0;
 
         let mapper = (control_byte_2 & 0b1111_0000) | (control_byte_1 >> 4);
 
-        let screen_mirroring = match (four_screen_flag, vertical_mirroring_flag) {
-            (true, _) => Mirroring::FourScreen,
-            (false, true) => Mirroring::Vertical,
-            (false, false) => Mirroring::Horizontal,
-        };
+        let screen_mirroring =
+            Mirroring::from_control_bits(four_screen_flag, vertical_mirroring_flag);
 
         let prg_rom_size = raw[4] as usize * PRG_ROM_PAGE_SIZE;
+        // Byte 8 counts 8KB pages; 0 means 8KB for compatibility. Headers with
+        // junk in bytes 12-15 (e.g. "DiskDude!") don't set it meaningfully
+        let prg_ram_pages = if raw[12..16].iter().all(|&byte| byte == 0) {
+            raw[8] as usize
+        } else {
+            0
+        };
+        let prg_ram_size = prg_ram_pages.max(1) * PRG_RAM_PAGE_SIZE;
         let chr_rom_size = raw[5] as usize * CHR_ROM_PAGE_SIZE;
 
-        let prg_rom_start = 16 + if trainer_flag { 512 } else { 0 };
+        let trainer = trainer_flag.then(|| {
+            let mut trainer = [0; TRAINER_SIZE];
+            trainer.copy_from_slice(&raw[16..16 + TRAINER_SIZE]);
+            trainer
+        });
+
+        let prg_rom_start = 16 + if trainer_flag { TRAINER_SIZE } else { 0 };
         let chr_rom_start = prg_rom_start + prg_rom_size;
 
+        let chr_is_ram = chr_rom_size == 0;
+        let chr_rom = if chr_is_ram {
+            vec![0; CHR_ROM_PAGE_SIZE]
+        } else {
+            raw[chr_rom_start..(chr_rom_start + chr_rom_size)].to_vec()
+        };
+
         Ok(Rom {
             prg_rom: raw[prg_rom_start..(prg_rom_start + prg_rom_size)].to_vec(),
-            chr_rom: raw[chr_rom_start..(chr_rom_start + chr_rom_size)].to_vec(),
+            chr_rom,
+            chr_is_ram,
             mapper,
             screen_mirroring,
+            has_battery: battery_ram_flag,
+            trainer,
+            prg_ram_size,
         })
     }
 
+    pub fn id(&self) -> RomId {
+        // The PRG length keeps the PRG/CHR boundary part of the identity
+        let prg_len = (self.prg_rom.len() as u64).to_le_bytes();
+        RomId(fnv1a(
+            prg_len
+                .into_iter()
+                .chain(self.prg_rom.iter().copied())
+                .chain(self.chr_rom.iter().copied()),
+        ))
+    }
+
+    // Whether the cartridge keeps its PRG RAM alive with a battery
+    pub fn is_battery_backed(&self) -> bool {
+        self.has_battery
+    }
+
+    // PRG RAM the cartridge provides, 8KB unless the header asks for more
+    pub fn sram_size(&self) -> usize {
+        self.prg_ram_size
+    }
+
+    // The 512 bytes meant for $7000-$71FF, if the image carries them
+    pub fn trainer(&self) -> Option<&[u8; TRAINER_SIZE]> {
+        self.trainer.as_ref()
+    }
+
     pub fn from_pc(pc: u16) -> Rom {
         let mut prg_rom = vec![0; 2 * PRG_ROM_PAGE_SIZE];
         prg_rom[0x7FFC] = (pc & 0xFF) as u8; // Store low byte of PC
@@ -65,8 +173,12 @@ impl Rom {
         Rom {
             prg_rom, // Default PRG-ROM
             chr_rom: vec![],
+            chr_is_ram: false,
             mapper: 0,
             screen_mirroring: Mirroring::Horizontal,
+            has_battery: false,
+            trainer: None,
+            prg_ram_size: PRG_RAM_PAGE_SIZE,
         }
     }
 
@@ -74,8 +186,20 @@ impl Rom {
         Rom {
             prg_rom: prg_rom.to_vec(),
             chr_rom: vec![0; CHR_ROM_PAGE_SIZE], // Default CHR-ROM
+            chr_is_ram: false,
             mapper: 0,
             screen_mirroring: Mirroring::Horizontal,
+            has_battery: false,
+            trainer: None,
+            prg_ram_size: PRG_RAM_PAGE_SIZE,
+        }
+    }
+
+    pub fn from_prg_with_chr(prg_rom: &[u8], chr_rom: &[u8], mirroring: Mirroring) -> Rom {
+        Rom {
+            chr_rom: chr_rom.to_vec(),
+            screen_mirroring: mirroring,
+            ..Rom::from_prg(prg_rom)
         }
     }
 
@@ -133,7 +257,7 @@ mod rom_tests {
         let rom = Rom::new(&rom_data).unwrap();
 
         assert_eq!(rom.prg_rom.len(), 2 * PRG_ROM_PAGE_SIZE);
-        assert_eq!(rom.chr_rom.len(), 1 * CHR_ROM_PAGE_SIZE);
+        assert_eq!(rom.chr_rom.len(), CHR_ROM_PAGE_SIZE);
         assert_eq!(rom.mapper, 0);
         assert_eq!(rom.screen_mirroring, Mirroring::Horizontal);
 
@@ -150,7 +274,9 @@ mod rom_tests {
 
         let result = Rom::new(&rom_data);
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "File is not in iNES file format");
+        let err = result.unwrap_err();
+        assert_eq!(err, RomError::InvalidTag);
+        assert_eq!(err.to_string(), "File is not in iNES file format");
     }
 
     #[test]
@@ -159,10 +285,38 @@ mod rom_tests {
 
         let result = Rom::new(&rom_data);
         assert!(result.is_err());
+        let err = result.unwrap_err();
+        assert_eq!(err, RomError::UnsupportedVersion);
+        assert_eq!(err.to_string(), "Only iNES 1.0 file format is supported");
+    }
+
+    #[test]
+    fn test_mirroring_from_control_bits() {
+        assert_eq!(
+            Mirroring::from_control_bits(false, false),
+            Mirroring::Horizontal
+        );
+        assert_eq!(
+            Mirroring::from_control_bits(false, true),
+            Mirroring::Vertical
+        );
         assert_eq!(
-            result.unwrap_err(),
-            "Only iNES 1.0 file format is supported"
+            Mirroring::from_control_bits(true, false),
+            Mirroring::FourScreen
         );
+        assert_eq!(
+            Mirroring::from_control_bits(true, true),
+            Mirroring::FourScreen
+        );
+    }
+
+    #[test]
+    fn test_mirroring_display() {
+        assert_eq!(Mirroring::Vertical.to_string(), "Vertical");
+        assert_eq!(Mirroring::Horizontal.to_string(), "Horizontal");
+        assert_eq!(Mirroring::FourScreen.to_string(), "Four-screen");
+        assert_eq!(Mirroring::SingleScreenA.to_string(), "Single-screen A");
+        assert_eq!(Mirroring::SingleScreenB.to_string(), "Single-screen B");
     }
 
     #[test]
@@ -222,6 +376,30 @@ mod rom_tests {
         assert_eq!(rom.mapper, 255);
     }
 
+    #[test]
+    fn test_from_prg_with_chr() {
+        let mut chr = vec![0; CHR_ROM_PAGE_SIZE];
+        chr[0x10] = 0x7E;
+        let rom = Rom::from_prg_with_chr(&[0xEA; 0x4000], &chr, Mirroring::Vertical);
+
+        let bus = crate::mem::bus::Bus::from_rom(rom);
+        let ppu = bus.ppu().unwrap();
+        assert_eq!(ppu.chr_rom[0x10], 0x7E);
+        assert_eq!(ppu.mirroring, Mirroring::Vertical);
+        assert!(!ppu.chr_is_ram);
+    }
+
+    #[test]
+    fn test_battery_backed() {
+        let rom_data = Rom::create_rom_data(1, 1, 0x00, 0b0000_0010, false);
+        let rom = Rom::new(&rom_data).unwrap();
+        assert!(rom.is_battery_backed());
+
+        let rom_data = Rom::create_rom_data(1, 1, 0x00, 0x00, false);
+        let rom = Rom::new(&rom_data).unwrap();
+        assert!(!rom.is_battery_backed());
+    }
+
     #[test]
     fn test_rom_with_trainer() {
         let rom_data = Rom::create_rom_data(1, 1, 0x00, 0b0000_0100, true);
@@ -235,6 +413,21 @@ mod rom_tests {
         assert!(rom.chr_rom.iter().all(|&x| x == 0xBB));
     }
 
+    #[test]
+    fn test_rom_trainer_bytes() {
+        let mut rom_data = Rom::create_rom_data(1, 1, 0x00, 0b0000_0100, true);
+        for (i, byte) in rom_data[16..16 + 512].iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        rom_data[16 + 512] = 0x4C; // First PRG byte
+        let rom = Rom::new(&rom_data).unwrap();
+
+        let trainer = rom.trainer().unwrap();
+        assert!(trainer.iter().enumerate().all(|(i, &x)| x == i as u8));
+        assert_eq!(rom.prg_rom[0], 0x4C);
+        assert!(rom.prg_rom[1..].iter().all(|&x| x == 0xAA));
+    }
+
     #[test]
     fn test_rom_without_trainer() {
         let rom_data = Rom::create_rom_data(1, 1, 0x00, 0b0000_0000, false);
@@ -242,6 +435,7 @@ mod rom_tests {
 
         assert_eq!(rom.prg_rom.len(), PRG_ROM_PAGE_SIZE);
         assert_eq!(rom.chr_rom.len(), CHR_ROM_PAGE_SIZE);
+        assert_eq!(rom.trainer(), None);
     }
 
     #[test]
@@ -250,7 +444,17 @@ mod rom_tests {
         let rom = Rom::new(&rom_data).unwrap();
 
         assert_eq!(rom.prg_rom.len(), 4 * PRG_ROM_PAGE_SIZE);
-        assert_eq!(rom.chr_rom.len(), 1 * CHR_ROM_PAGE_SIZE);
+        assert_eq!(rom.chr_rom.len(), CHR_ROM_PAGE_SIZE);
+    }
+
+    #[test]
+    fn test_zero_chr_pages_allocates_chr_ram() {
+        let rom_data = Rom::create_rom_data(1, 0, 0x00, 0x00, false);
+        let rom = Rom::new(&rom_data).unwrap();
+
+        assert!(rom.chr_is_ram);
+        assert_eq!(rom.chr_rom.len(), CHR_ROM_PAGE_SIZE);
+        assert!(rom.chr_rom.iter().all(|&x| x == 0));
     }
 
     #[test]
@@ -258,7 +462,7 @@ mod rom_tests {
         let rom_data = Rom::create_rom_data(1, 3, 0x00, 0x00, false);
         let rom = Rom::new(&rom_data).unwrap();
 
-        assert_eq!(rom.prg_rom.len(), 1 * PRG_ROM_PAGE_SIZE);
+        assert_eq!(rom.prg_rom.len(), PRG_ROM_PAGE_SIZE);
         assert_eq!(rom.chr_rom.len(), 3 * CHR_ROM_PAGE_SIZE);
     }
 
@@ -271,4 +475,42 @@ mod rom_tests {
         let result = std::panic::catch_unwind(|| Rom::new(&short_data));
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_rom_id() {
+        let data = Rom::create_rom_data(1, 1, 0x00, 0x00, false);
+        let rom = Rom::new(&data).unwrap();
+        assert_eq!(rom.id(), Rom::new(&data).unwrap().id());
+
+        let mut modified = rom.clone();
+        modified.chr_rom[0x100] ^= 1;
+        assert_ne!(modified.id(), rom.id());
+
+        // Same bytes split differently between PRG and CHR
+        let mut shifted = rom.clone();
+        shifted.chr_rom.insert(0, shifted.prg_rom.pop().unwrap());
+        assert_ne!(shifted.id(), rom.id());
+    }
+
+    #[test]
+    fn test_sram_size() {
+        let rom = Rom::new(&Rom::create_rom_data(1, 1, 0x00, 0x00, false)).unwrap();
+        assert_eq!(rom.sram_size(), 0x2000);
+
+        let mut rom_data = Rom::create_rom_data(1, 1, 0x00, 0x00, false);
+        rom_data[8] = 4; // 32KB of PRG RAM
+        let rom = Rom::new(&rom_data).unwrap();
+        assert_eq!(rom.sram_size(), 0x8000);
+
+        let bus = crate::mem::bus::Bus::from_rom(rom);
+        assert_eq!(bus.prg_ram().len(), 0x8000);
+    }
+
+    #[test]
+    fn test_sram_size_ignores_dirty_header() {
+        let mut rom_data = Rom::create_rom_data(1, 1, 0x00, 0x00, false);
+        rom_data[8..16].copy_from_slice(b"iskDude!"); // Byte 8 reads as 0x69 pages
+        let rom = Rom::new(&rom_data).unwrap();
+        assert_eq!(rom.sram_size(), 0x2000);
+    }
 }
@@ -0,0 +1,116 @@
+use crate::ppu::{PPU, sprite::Sprite};
+
+// Read-only views of PPU memory for debuggers and tools. Nametables are
+// resolved through the current mirroring, arrays are indexed [row][column]
+
+// Tile index and attribute palette (0-3) of a cell in one of the four
+// logical nametables
+pub fn nametable_tile(ppu: &PPU, nametable: u8, col: u8, row: u8) -> (u8, u8) {
+    let base = 0x2000 + (nametable as u16 & 0b11) * 0x400;
+    let tile_addr = base + row as u16 * 32 + col as u16;
+    let attr_addr = base + 0x3C0 + (row as u16 / 4) * 8 + col as u16 / 4;
+
+    let tile = ppu.vram[ppu.mirror_vram_addr(tile_addr) as usize];
+    let attr_byte = ppu.vram[ppu.mirror_vram_addr(attr_addr) as usize];
+    let shift = (row % 4 / 2) * 4 + (col % 4 / 2) * 2;
+    (tile, (attr_byte >> shift) & 0b11)
+}
+
+// Tile indices of one of the four logical nametables
+pub fn nametable_dump(ppu: &PPU, nametable: u8) -> [[u8; 32]; 30] {
+    std::array::from_fn(|row| {
+        std::array::from_fn(|col| nametable_tile(ppu, nametable, col as u8, row as u8).0)
+    })
+}
+
+// Palette (0-3) of each 16x16 pixel area of a nametable
+pub fn attribute_dump(ppu: &PPU, nametable: u8) -> [[u8; 16]; 15] {
+    std::array::from_fn(|row| {
+        std::array::from_fn(|col| nametable_tile(ppu, nametable, col as u8 * 2, row as u8 * 2).1)
+    })
+}
+
+pub fn oam(ppu: &PPU) -> &[u8; 256] {
+    &ppu.oam_data
+}
+
+pub fn oam_dump(ppu: &PPU) -> [Sprite; 64] {
+    std::array::from_fn(|i| Sprite::from_bytes(&ppu.oam_data[i * 4..i * 4 + 4]))
+}
+
+// Four background palettes followed by four sprite palettes
+pub fn palette_dump(ppu: &PPU) -> [[u8; 4]; 8] {
+    std::array::from_fn(|palette| {
+        std::array::from_fn(|entry| ppu.palette_table[palette * 4 + entry])
+    })
+}
+
+#[cfg(test)]
+mod debug_tests {
+    use super::*;
+    use crate::mem::rom::Mirroring;
+
+    #[test]
+    fn test_nametable_tile() {
+        let mut ppu = PPU::new(vec![0; 0x2000], Mirroring::Vertical);
+
+        // Nametable 1, column 6, row 7
+        ppu.vram[0x400 + 7 * 32 + 6] = 0x7B;
+        // Attribute byte for columns 4-7, rows 4-7; bottom-right quadrant = 0b10
+        ppu.vram[0x400 + 0x3C0 + 8 + 1] = 0b10_01_00_11;
+
+        assert_eq!(nametable_tile(&ppu, 1, 6, 7), (0x7B, 0b10));
+        assert_eq!(nametable_tile(&ppu, 1, 4, 4), (0x00, 0b11));
+        // Nametable 3 mirrors nametable 1 with vertical mirroring
+        assert_eq!(nametable_tile(&ppu, 3, 6, 7), (0x7B, 0b10));
+        assert_eq!(nametable_tile(&ppu, 0, 6, 7), (0x00, 0b00));
+    }
+
+    #[test]
+    fn test_nametable_and_attribute_dump() {
+        let mut ppu = PPU::new(vec![0; 0x2000], Mirroring::Vertical);
+        ppu.vram[0x400 + 2 * 32 + 5] = 0x7B; // Nametable 1, row 2, column 5
+        ppu.vram[0x400 + 0x3C0 + 9] = 0b1110_0100; // Attribute byte of rows 4-7, columns 4-7
+
+        let tiles = nametable_dump(&ppu, 1);
+        assert_eq!(tiles[2][5], 0x7B);
+        assert_eq!(tiles.iter().flatten().filter(|&&tile| tile != 0).count(), 1);
+        // Vertical mirroring: nametable 3 shows the same VRAM as 1
+        assert_eq!(nametable_dump(&ppu, 3), tiles);
+        assert_eq!(nametable_dump(&ppu, 0)[2][5], 0);
+
+        let attributes = attribute_dump(&ppu, 1);
+        assert_eq!(attributes[2][2], 0b00);
+        assert_eq!(attributes[2][3], 0b01);
+        assert_eq!(attributes[3][2], 0b10);
+        assert_eq!(attributes[3][3], 0b11);
+        assert_eq!(attributes[0][0], 0);
+    }
+
+    #[test]
+    fn test_oam_and_palette_dump() {
+        let mut ppu = PPU::new(vec![0; 0x2000], Mirroring::Horizontal);
+        let mut oam = [0; 256];
+        oam[8..12].copy_from_slice(&[0x20, 0x05, 0x41, 0x30]);
+        ppu.load_oam(&oam);
+        ppu.palette_table[0x00] = 0x0F;
+        ppu.palette_table[0x06] = 0x16;
+        ppu.palette_table[0x1F] = 0x2A;
+
+        let sprites = oam_dump(&ppu);
+        assert_eq!(
+            sprites[2],
+            Sprite {
+                y: 0x20,
+                tile: 0x05,
+                attributes: 0x41,
+                x: 0x30,
+            }
+        );
+
+        let palettes = palette_dump(&ppu);
+        assert_eq!(palettes[0], [0x0F, 0, 0, 0]);
+        assert_eq!(palettes[1][2], 0x16);
+        assert_eq!(palettes[7][3], 0x2A);
+    }
+}
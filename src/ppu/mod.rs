@@ -4,22 +4,51 @@ use crate::{
         PPUMASK, PPUSTATUS, control_reg::PPUCTRL, oam_address::OAMADDRESS, ppu_address::PPUADDRESS,
         scroll::PPUSCROLL,
     },
+    ppu::sprite::SpriteOverflowMode,
+    region::Region,
 };
 
+pub mod debug;
 pub mod register;
+pub mod sprite;
+
+// After power-on/reset the PPU ignores $2000/$2001/$2005/$2006 for this long
+const WARMUP_CPU_CYCLES: u32 = 29658;
+
+// Timing position and status flags captured together without the side
+// effects of a $2002 read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PpuStatusSnapshot {
+    pub scanline: u32,
+    pub dot: u32,
+    pub vblank: bool,
+    pub sprite0: bool,
+    pub overflow: bool,
+    pub nmi_pending: bool,
+}
 
 #[allow(dead_code)]
+#[derive(Clone)]
 pub struct PPU {
     pub chr_rom: Vec<u8>,        // $0000–$1FFF (8KB CHR ROM)
+    pub chr_is_ram: bool,        // Pattern tables are writable CHR RAM
     pub vram: [u8; 2048],        // $2000–$2FFF (2KB VRAM, mirrored to 4KB)
     pub palette_table: [u8; 32], // $3F00–$3FFF (32 bytes for palettes, mirrored)
     pub oam_data: [u8; 256],
 
     pub mirroring: Mirroring,
-
-    cycle: u32,        // Current cycle in the PPU (0-340)
-    scanline: u32,     // Current scanline in the PPU (0-261)
-    nmi_pending: bool, // NMI flag for VBlank
+    pub sprite_overflow_mode: SpriteOverflowMode,
+    region: Region,
+
+    cycle: u32,            // Current cycle in the PPU (0-340)
+    scanline: u32,         // Current scanline in the PPU (0-261, 0-311 on PAL)
+    nmi_pending: bool,     // NMI flag for VBlank
+    suppress_vblank: bool, // $2002 was read the dot before VBlank would set
+    warmup_dots: u32,      // Remaining dots of the post-reset warmup
+    // Background pattern table each visible scanline fetched its tiles from
+    bg_pattern_lines: [u16; 240],
+    // Same for the 8x8 sprite pattern table
+    sprite_pattern_lines: [u16; 240],
 
     ctrl: PPUCTRL,
     mask: PPUMASK,
@@ -28,25 +57,36 @@ pub struct PPU {
     scroll: PPUSCROLL,
     ppu_addr: PPUADDRESS,
     ppu_data_buf: u8,
+    io_latch: u8, // Last value on the CPU-PPU data bus, read back as open bus
 
     // Internal registers
     v_reg: u16,  // Current VRAM address (15 bits)
     t_reg: u16,  // Temporary VRAM address (15 bits)
     x_reg: u8,   // Fine X scroll (3 bits)
     w_reg: bool, // Write toggle (0 or 1)
+
+    #[cfg(feature = "ppu-trace")]
+    ppu_write_log: Vec<(u32, u32, u16, u8)>, // (scanline, dot, register, value)
 }
 
 impl PPU {
     pub fn new(chr_rom: Vec<u8>, mirroring: Mirroring) -> Self {
         PPU {
             chr_rom,
+            chr_is_ram: false,
             mirroring,
+            sprite_overflow_mode: SpriteOverflowMode::Clean,
+            region: Region::Ntsc,
             vram: [0; 2048],
             oam_data: [0; 64 * 4],
             palette_table: [0; 32],
             cycle: 0,
             scanline: 0,
             nmi_pending: false,
+            suppress_vblank: false,
+            warmup_dots: 0,
+            bg_pattern_lines: [0; 240],
+            sprite_pattern_lines: [0; 240],
             ctrl: PPUCTRL::new(),
             mask: PPUMASK::from_bits_truncate(0),
             status: PPUSTATUS::from_bits_truncate(0),
@@ -54,55 +94,271 @@ impl PPU {
             scroll: PPUSCROLL::new(),
             ppu_addr: PPUADDRESS::new(),
             ppu_data_buf: 0,
+            io_latch: 0,
             v_reg: 0,
             t_reg: 0,
             x_reg: 0,
             w_reg: false,
+            #[cfg(feature = "ppu-trace")]
+            ppu_write_log: Vec::new(),
         }
     }
 
+    #[cfg(feature = "ppu-trace")]
+    pub fn log_register_write(&mut self, register: u16, value: u8) {
+        self.ppu_write_log
+            .push((self.scanline, self.cycle, register, value));
+    }
+
+    #[cfg(feature = "ppu-trace")]
+    pub fn take_ppu_write_log(&mut self) -> Vec<(u32, u32, u16, u8)> {
+        std::mem::take(&mut self.ppu_write_log)
+    }
+
+    // Replaces the pattern table backing store, e.g. to swap CHR banks by hand
+    pub fn set_chr(&mut self, chr: Vec<u8>) {
+        self.chr_rom = chr;
+    }
+
+    pub fn set_mirroring(&mut self, mirroring: Mirroring) {
+        self.mirroring = mirroring;
+    }
+
     fn increment_vram_addr(&mut self) {
         self.ppu_addr.increment(self.ctrl.vram_addr_increment());
     }
 
-    pub fn tick(&mut self, count: u32) {
-        self.cycle += count;
-        while self.cycle >= 341 {
-            self.cycle -= 341;
-            self.scanline += 1;
+    // Returns true when a frame finished rendering (VBlank started) during the tick
+    pub fn tick(&mut self, count: u32) -> bool {
+        let mut frame_complete = false;
+        self.warmup_dots = self.warmup_dots.saturating_sub(count);
+        for _ in 0..count {
+            self.cycle += 1;
+            if self.cycle == 341 {
+                self.cycle = 0;
+                frame_complete |= self.next_scanline();
+            }
+            // The scroll pipeline only runs while rendering; toggling $2001
+            // mid-scanline pauses and resumes it
+            if self.is_rendering() {
+                self.update_v_reg();
+            }
+        }
+        frame_complete
+    }
+
+    fn next_scanline(&mut self) -> bool {
+        let mut frame_complete = false;
+        self.scanline += 1;
 
-            if self.scanline == 241 {
+        if self.scanline == 241 {
+            frame_complete = true;
+            if !std::mem::take(&mut self.suppress_vblank) {
                 self.status.set(PPUSTATUS::VBLANK, true);
                 if self.ctrl.contains(PPUCTRL::GENERATE_NMI) {
                     self.nmi_pending = true;
                 }
             }
-            if self.scanline >= 262 {
-                self.scanline = 0;
-                self.status.set(PPUSTATUS::VBLANK, false);
-                self.clear_nmi_flag();
-            }
         }
+        if self.scanline >= self.region.scanlines_per_frame() {
+            self.scanline = 0;
+            self.status.set(PPUSTATUS::VBLANK, false);
+            self.status.set(PPUSTATUS::SPRITE_OVERFLOW, false);
+            self.clear_nmi_flag();
+        }
+        if self.scanline < 240 {
+            self.bg_pattern_lines[self.scanline as usize] = self.ctrl.background_pattern_addr();
+            self.sprite_pattern_lines[self.scanline as usize] = self.ctrl.sprite_pattern_addr();
+        }
+        if self.scanline < 240
+            && self.rendering_enabled()
+            && self.evaluate_sprite_overflow(self.scanline)
+        {
+            self.status.set(PPUSTATUS::SPRITE_OVERFLOW, true);
+        }
+        // The sprite tile fetches (dots 257-320) leave OAMADDR at 0
+        if self.is_rendering() {
+            self.oam_addr.update(0);
+        }
+        frame_complete
+    }
+
+    // v register updates at the current dot of a rendering scanline
+    fn update_v_reg(&mut self) {
+        let dot = self.cycle;
+        if dot.is_multiple_of(8) && ((1..=256).contains(&dot) || (321..=336).contains(&dot)) {
+            self.increment_coarse_x();
+        }
+        if dot == 256 {
+            self.increment_y();
+        }
+        if dot == 257 {
+            // Horizontal bits (coarse X and nametable X) from t
+            self.v_reg = (self.v_reg & !0x041F) | (self.t_reg & 0x041F);
+        }
+        if self.scanline == self.pre_render_scanline() && (280..=304).contains(&dot) {
+            // Vertical bits (fine Y, nametable Y and coarse Y) from t
+            self.v_reg = (self.v_reg & !0x7BE0) | (self.t_reg & 0x7BE0);
+        }
+    }
+
+    fn increment_coarse_x(&mut self) {
+        if self.v_reg & 0x001F == 31 {
+            self.v_reg &= !0x001F;
+            self.v_reg ^= 0x0400; // Next horizontal nametable
+        } else {
+            self.v_reg += 1;
+        }
+    }
+
+    fn increment_y(&mut self) {
+        if self.v_reg & 0x7000 != 0x7000 {
+            self.v_reg += 0x1000; // Fine Y
+            return;
+        }
+        self.v_reg &= !0x7000;
+        let mut coarse_y = (self.v_reg & 0x03E0) >> 5;
+        if coarse_y == 29 {
+            coarse_y = 0;
+            self.v_reg ^= 0x0800; // Next vertical nametable
+        } else if coarse_y == 31 {
+            coarse_y = 0; // Out of range rows wrap without switching nametables
+        } else {
+            coarse_y += 1;
+        }
+        self.v_reg = (self.v_reg & !0x03E0) | (coarse_y << 5);
+    }
+
+    // Starts the post-reset warmup. Console power-on does this; a bare PPU
+    // starts without one so tests can program it right away
+    pub fn begin_warmup(&mut self) {
+        self.warmup_dots = WARMUP_CPU_CYCLES * 3;
+    }
+
+    pub fn end_warmup(&mut self) {
+        self.warmup_dots = 0;
+    }
+
+    pub fn in_warmup(&self) -> bool {
+        self.warmup_dots > 0
+    }
+
+    // Address the next $2007 access will use
+    pub fn vram_addr(&self) -> u16 {
+        self.ppu_addr.get()
+    }
+
+    pub fn scanline(&self) -> u32 {
+        self.scanline
+    }
+
+    // Reads the VBlank flag without the side effects of a $2002 read
+    pub fn in_vblank(&self) -> bool {
+        self.status.is_vblank()
+    }
+
+    pub fn status_snapshot(&self) -> PpuStatusSnapshot {
+        PpuStatusSnapshot {
+            scanline: self.scanline,
+            dot: self.cycle,
+            vblank: self.status.contains(PPUSTATUS::VBLANK),
+            sprite0: self.status.contains(PPUSTATUS::SPRITE_0_HIT),
+            overflow: self.status.contains(PPUSTATUS::SPRITE_OVERFLOW),
+            nmi_pending: self.nmi_pending,
+        }
+    }
+
+    // Pattern table 8x8 sprites on `scanline` were fetched from
+    pub(crate) fn sprite_pattern_addr(&self, scanline: usize) -> u16 {
+        self.sprite_pattern_lines[scanline]
+    }
+
+    pub(crate) fn sprite_size(&self) -> u8 {
+        self.ctrl.sprite_size()
+    }
+
+    pub(crate) fn sprites_enabled(&self) -> bool {
+        self.mask.contains(PPUMASK::RENDER_SPRITE)
+    }
+
+    // Pattern table the background of `scanline` was fetched from, which
+    // follows $2000 writes made while the frame was being drawn
+    pub(crate) fn background_pattern_addr(&self, scanline: usize) -> u16 {
+        self.bg_pattern_lines[scanline]
     }
 
     pub fn write_to_ppu_addr(&mut self, value: u8) {
+        // The write is dropped during warmup but still flips the w latch
+        if self.in_warmup() {
+            self.w_reg = !self.w_reg;
+            return;
+        }
+        if !self.w_reg {
+            self.t_reg = (self.t_reg & 0x00FF) | (((value & 0x3F) as u16) << 8);
+        } else {
+            self.t_reg = (self.t_reg & 0xFF00) | value as u16;
+            self.v_reg = self.t_reg;
+        }
         self.ppu_addr.update(value, &mut self.w_reg);
     }
 
     pub fn write_to_ctrl(&mut self, value: u8) {
+        if self.in_warmup() {
+            return;
+        }
         let generate_nmi_check = self.ctrl.contains(PPUCTRL::GENERATE_NMI)
             && !PPUCTRL::from_bits_truncate(value).contains(PPUCTRL::GENERATE_NMI);
         self.ctrl.update(value);
+        self.t_reg = (self.t_reg & !0x0C00) | (((value & 0b11) as u16) << 10);
+        // Lines already fetched this frame keep the pattern tables they used.
+        // The finished frame stays intact on the post-render line; the next
+        // one starts over once VBlank has begun
+        let first_line = if self.scanline > 240 {
+            0
+        } else {
+            self.scanline as usize
+        };
+        self.bg_pattern_lines[first_line..].fill(self.ctrl.background_pattern_addr());
+        self.sprite_pattern_lines[first_line..].fill(self.ctrl.sprite_pattern_addr());
         if generate_nmi_check && self.status.contains(PPUSTATUS::VBLANK) {
             self.nmi_pending = true;
         }
     }
 
+    pub fn write_to_mask(&mut self, value: u8) {
+        if self.in_warmup() {
+            return;
+        }
+        self.mask = PPUMASK::from_bits_truncate(value);
+    }
+
+    pub fn base_nametable(&self) -> u8 {
+        self.ctrl.base_nametable()
+    }
+
+    pub fn ctrl_bits(&self) -> u8 {
+        self.ctrl.bits()
+    }
+
+    pub fn mask_bits(&self) -> u8 {
+        self.mask.bits()
+    }
+
+    // Red, green and blue emphasis from $2001 bits 5-7, as bits 0-2
+    pub fn emphasis(&self) -> u8 {
+        self.mask.bits() >> 5
+    }
+
     pub fn write_to_data(&mut self, value: u8) {
         let addr = self.ppu_addr.get();
         self.increment_vram_addr();
 
         match addr {
+            0..=0x1fff if self.chr_is_ram => {
+                if let Some(index) = self.chr_index(addr) {
+                    self.chr_rom[index] = value;
+                }
+            }
             0..=0x1fff => {
                 println!("attempt to write to chr rom space {}", addr)
             }
@@ -122,19 +378,132 @@ impl PPU {
         }
     }
 
+    pub fn write_to_oam_addr(&mut self, value: u8) {
+        self.oam_addr.update(value);
+    }
+
     pub fn write_to_oam_data(&mut self, value: u8) {
+        // Writes during rendering are dropped, the address still moves a sprite
+        if self.is_rendering() {
+            self.oam_addr.increment_sprite();
+            return;
+        }
         let addr = self.oam_addr.get();
         self.oam_addr.increment();
         self.oam_data[addr as usize] = value;
     }
 
+    fn rendering_enabled(&self) -> bool {
+        self.mask
+            .intersects(PPUMASK::RENDER_BACKGROUND | PPUMASK::RENDER_SPRITE)
+    }
+
+    // Visible and pre-render scanlines with background or sprites enabled
+    fn is_rendering(&self) -> bool {
+        self.rendering_enabled()
+            && (self.scanline < 240 || self.scanline == self.pre_render_scanline())
+    }
+
+    fn pre_render_scanline(&self) -> u32 {
+        self.region.scanlines_per_frame() - 1
+    }
+
+    // PAL frames run 50 extra VBlank scanlines
+    pub fn set_region(&mut self, region: Region) {
+        self.region = region;
+    }
+
+    // Whether sprite evaluation for `scanline` raises the overflow flag.
+    // Evaluation starts at the sprite OAMADDR points to (the low two bits
+    // are ignored) and stops at the end of OAM without wrapping
+    pub fn evaluate_sprite_overflow(&self, scanline: u32) -> bool {
+        let height = self.ctrl.sprite_size() as u32;
+        let in_range = |y: u8| scanline >= y as u32 && scanline < y as u32 + height;
+        let start = self.oam_addr.get() as usize / 4;
+
+        match self.sprite_overflow_mode {
+            SpriteOverflowMode::Clean => {
+                (start..64)
+                    .filter(|n| in_range(self.oam_data[n * 4]))
+                    .count()
+                    > 8
+            }
+            SpriteOverflowMode::Hardware => {
+                let mut n = start;
+                let mut found = 0;
+                while n < 64 && found < 8 {
+                    if in_range(self.oam_data[n * 4]) {
+                        found += 1;
+                    }
+                    n += 1;
+                }
+
+                // After eight hits the byte offset m is wrongly incremented
+                // along with n, so the Y check walks OAM diagonally
+                let mut m = 0;
+                while found == 8 && n < 64 {
+                    if in_range(self.oam_data[n * 4 + m]) {
+                        return true;
+                    }
+                    n += 1;
+                    m = (m + 1) & 0b11;
+                }
+                false
+            }
+        }
+    }
+
     pub fn write_to_scroll(&mut self, value: u8) {
+        // Same as $2006: ignored during warmup apart from the latch toggle
+        if self.in_warmup() {
+            self.w_reg = !self.w_reg;
+            return;
+        }
+        if !self.w_reg {
+            self.t_reg = (self.t_reg & !0x001F) | (value >> 3) as u16;
+            self.x_reg = value & 0b111;
+        } else {
+            self.t_reg = (self.t_reg & !0x73E0)
+                | (((value & 0b111) as u16) << 12)
+                | (((value & 0b1111_1000) as u16) << 2);
+        }
         self.scroll.update(value, &mut self.w_reg);
     }
 
+    // Effective (x, y) scroll in pixels across the 512x480 nametable space,
+    // decoded from v (yyy NN YYYYY XXXXX) and fine X
+    pub fn current_scroll(&self) -> (u16, u16) {
+        let coarse_x = self.v_reg & 0x1F;
+        let coarse_y = (self.v_reg >> 5) & 0x1F;
+        let nametable = (self.v_reg >> 10) & 0b11;
+        let fine_y = (self.v_reg >> 12) & 0b111;
+
+        let x = (nametable & 0b01) * 256 + coarse_x * 8 + self.x_reg as u16;
+        let y = (nametable >> 1) * 240 + coarse_y * 8 + fine_y;
+        (x, y)
+    }
+
     pub fn read_status(&mut self) -> u8 {
+        // Reading one dot before VBlank sets reads it clear and skips the flag
+        // and NMI for the whole frame
+        if self.scanline == 240 && self.cycle == 340 {
+            self.suppress_vblank = true;
+        }
         self.w_reg = false;
-        self.status.bits()
+        // Only the top three bits are driven, the rest is open bus
+        let value = (self.status.bits() & 0b1110_0000) | (self.io_latch & 0b0001_1111);
+        self.io_latch = value;
+        value
+    }
+
+    // Called by the bus with every value read from or written to a register
+    pub fn latch_io(&mut self, value: u8) {
+        self.io_latch = value;
+    }
+
+    // Write-only registers read back whatever is left on the data bus
+    pub fn io_latch(&self) -> u8 {
+        self.io_latch
     }
 
     pub fn read_data(&mut self) -> u8 {
@@ -144,7 +513,7 @@ impl PPU {
         match addr {
             0..=0x1fff => {
                 let result = self.ppu_data_buf;
-                self.ppu_data_buf = self.chr_rom[addr as usize];
+                self.ppu_data_buf = self.read_chr(addr);
                 result
             }
             0x2000..=0x2fff => {
@@ -164,9 +533,30 @@ impl PPU {
         }
     }
 
+    // Resolves a pattern table address against the currently selected CHR,
+    // so $2007 reads buffer from whatever bank is mapped in at read time.
+    // Smaller CHR ROMs are mirrored across the pattern tables
+    fn chr_index(&self, addr: u16) -> Option<usize> {
+        if self.chr_rom.is_empty() {
+            return None;
+        }
+        Some(addr as usize % self.chr_rom.len())
+    }
+
+    // Without any CHR the read falls back to 0
+    fn read_chr(&self, addr: u16) -> u8 {
+        self.chr_index(addr).map_or(0, |index| self.chr_rom[index])
+    }
+
+    pub fn load_oam(&mut self, data: &[u8; 256]) {
+        self.oam_data = *data;
+    }
+
     pub fn read_oam_data(&mut self) -> u8 {
         let addr = self.oam_addr.get();
-        self.oam_data[addr as usize]
+        let value = self.oam_data[addr as usize];
+        // OAM has no storage for bits 2-4 of the attribute byte, they read as 0
+        if addr % 4 == 2 { value & 0xE3 } else { value }
     }
 
     pub fn get_nmi_flag(&self) -> bool {
@@ -186,6 +576,8 @@ impl PPU {
             (Mirroring::Horizontal, 2) => vram_index - 0x400,
             (Mirroring::Horizontal, 1) => vram_index - 0x400,
             (Mirroring::Horizontal, 3) => vram_index - 0x800,
+            (Mirroring::SingleScreenA, _) => vram_index & 0x3FF,
+            (Mirroring::SingleScreenB, _) => (vram_index & 0x3FF) + 0x400,
             _ => vram_index,
         }
     }
@@ -194,7 +586,7 @@ impl PPU {
 #[cfg(test)]
 mod ppu_tests {
     use super::*;
-    use crate::mem::rom::Mirroring;
+    use crate::{mem::rom::Mirroring, ppu::sprite::Sprite};
 
     fn create_test_ppu(mirroring: Mirroring) -> PPU {
         let chr_rom = vec![0x42; 0x2000]; // 8KB CHR ROM filled with 0x42
@@ -214,7 +606,7 @@ mod ppu_tests {
         assert_eq!(ppu.v_reg, 0);
         assert_eq!(ppu.t_reg, 0);
         assert_eq!(ppu.x_reg, 0);
-        assert_eq!(ppu.w_reg, false);
+        assert!(!ppu.w_reg);
         assert_eq!(ppu.ppu_data_buf, 0);
 
         // Check that arrays are zero-initialized
@@ -223,18 +615,74 @@ mod ppu_tests {
         assert!(ppu.palette_table.iter().all(|&x| x == 0));
     }
 
+    #[test]
+    fn test_status_snapshot_at_vblank_start() {
+        let mut ppu = create_test_ppu(Mirroring::Vertical);
+        ppu.write_to_ctrl(0x80);
+
+        ppu.tick(241 * 341 - 1);
+        assert_eq!(
+            ppu.status_snapshot(),
+            PpuStatusSnapshot {
+                scanline: 240,
+                dot: 340,
+                vblank: false,
+                sprite0: false,
+                overflow: false,
+                nmi_pending: false,
+            }
+        );
+
+        ppu.tick(1);
+        let expected = PpuStatusSnapshot {
+            scanline: 241,
+            dot: 0,
+            vblank: true,
+            sprite0: false,
+            overflow: false,
+            nmi_pending: true,
+        };
+        assert_eq!(ppu.status_snapshot(), expected);
+        // Taking a snapshot does not clear VBlank like a $2002 read would
+        assert_eq!(ppu.status_snapshot(), expected);
+    }
+
     #[test]
     fn test_write_to_ctrl() {
         let mut ppu = create_test_ppu(Mirroring::Vertical);
 
         // Test writing different control values
         ppu.write_to_ctrl(0b10110101);
-        assert_eq!(ppu.ctrl.bits(), 0b10110101);
+        assert_eq!(ppu.ctrl_bits(), 0b10110101);
 
         ppu.write_to_ctrl(0x00);
-        assert_eq!(ppu.ctrl.bits(), 0x00);
+        assert_eq!(ppu.ctrl_bits(), 0x00);
         ppu.write_to_ctrl(0xFF);
-        assert_eq!(ppu.ctrl.bits(), 0xFF);
+        assert_eq!(ppu.ctrl_bits(), 0xFF);
+    }
+
+    #[test]
+    fn test_ctrl_write_on_post_render_line_keeps_finished_frame() {
+        let mut ppu = create_test_ppu(Mirroring::Vertical);
+
+        ppu.tick(240 * 341); // Post-render line 240
+        ppu.write_to_ctrl(0b0001_1000);
+        assert_eq!(ppu.background_pattern_addr(0), 0);
+        assert_eq!(ppu.sprite_pattern_addr(0), 0);
+
+        ppu.tick(341); // VBlank line 241
+        ppu.write_to_ctrl(0b0001_1000);
+        assert_eq!(ppu.background_pattern_addr(0), 0x1000);
+        assert_eq!(ppu.sprite_pattern_addr(0), 0x1000);
+    }
+
+    #[test]
+    fn test_write_to_mask() {
+        let mut ppu = create_test_ppu(Mirroring::Vertical);
+        assert_eq!(ppu.mask_bits(), 0);
+
+        ppu.write_to_mask(0b0001_1110);
+        assert_eq!(ppu.mask_bits(), 0b0001_1110);
     }
 
     #[test]
@@ -248,6 +696,25 @@ mod ppu_tests {
         assert_eq!(ppu.ppu_addr.get(), 0x2000);
     }
 
+    #[test]
+    fn test_write_to_ppu_addr_wraps_to_14_bits() {
+        let mut ppu = create_test_ppu(Mirroring::Vertical);
+
+        ppu.write_to_ppu_addr(0x40);
+        ppu.write_to_ppu_addr(0x00);
+        assert_eq!(ppu.ppu_addr.get(), 0x0000);
+        assert_eq!(ppu.v_reg, 0x0000);
+
+        // 0x6305 lands on nametable address 0x2305
+        ppu.write_to_ppu_addr(0x63);
+        ppu.write_to_ppu_addr(0x05);
+        assert_eq!(ppu.ppu_addr.get(), 0x2305);
+        assert_eq!(ppu.v_reg, 0x2305);
+
+        ppu.write_to_data(0x77);
+        assert_eq!(ppu.vram[0x305], 0x77);
+    }
+
     #[test]
     fn test_write_to_vram() {
         let mut ppu = create_test_ppu(Mirroring::Vertical);
@@ -296,6 +763,58 @@ mod ppu_tests {
         assert_eq!(second_read, 0x42); // Our test CHR ROM is filled with 0x42
     }
 
+    #[test]
+    fn test_set_chr_swaps_rendered_tiles() {
+        use crate::render::{self, frame::Frame};
+
+        let mut ppu = create_test_ppu(Mirroring::Horizontal);
+        ppu.palette_table[1] = 0x30;
+        let mut frame = Frame::new();
+
+        // 0x42 in both planes gives color 3 pixels at columns 1 and 6
+        render::render(&ppu, &mut frame);
+        let before = frame.data.clone();
+
+        let mut chr = vec![0; 0x2000];
+        chr[0] = 0x80; // Tile 0, row 0: leftmost pixel uses color 1
+        ppu.set_chr(chr);
+        render::render(&ppu, &mut frame);
+
+        assert_ne!(frame.data, before);
+        assert_eq!(&frame.data[0..3], &[0xFF, 0xFF, 0xFF]);
+        assert_eq!(ppu.chr_rom[0], 0x80);
+    }
+
+    #[test]
+    fn test_buffered_chr_read_uses_selected_bank() {
+        let mut ppu = PPU::new(vec![0x11; 0x2000], Mirroring::Horizontal);
+        ppu.write_to_ppu_addr(0x00);
+        ppu.write_to_ppu_addr(0x10);
+        ppu.read_data(); // Buffers $0010 from the first bank
+
+        ppu.set_chr(vec![0x22; 0x2000]); // Switch banks between reads
+        assert_eq!(ppu.read_data(), 0x11); // Filled before the switch
+        assert_eq!(ppu.read_data(), 0x22); // Refilled from the new bank
+    }
+
+    #[test]
+    fn test_read_from_small_chr_rom() {
+        let mut chr_rom = vec![0; 0x1000];
+        chr_rom[0x0800] = 0x5A;
+        let mut ppu = PPU::new(chr_rom, Mirroring::Horizontal);
+
+        ppu.write_to_ppu_addr(0x18);
+        ppu.write_to_ppu_addr(0x00);
+        ppu.read_data(); // Dummy read
+        assert_eq!(ppu.read_data(), 0x5A); // 0x1800 mirrors 0x0800
+
+        let mut ppu = PPU::new(vec![], Mirroring::Horizontal);
+        ppu.write_to_ppu_addr(0x18);
+        ppu.write_to_ppu_addr(0x00);
+        ppu.read_data();
+        assert_eq!(ppu.read_data(), 0);
+    }
+
     #[test]
     fn test_read_from_vram() {
         let mut ppu = create_test_ppu(Mirroring::Vertical);
@@ -380,6 +899,48 @@ mod ppu_tests {
         assert_eq!(ppu.mirror_vram_addr(0x2FFF), 0x07FF);
     }
 
+    #[test]
+    fn test_mirror_vram_addr_single_screen() {
+        let mut ppu = create_test_ppu(Mirroring::SingleScreenA);
+
+        // Single-screen A maps every nametable to the first 1KB
+        for name_table in 0..4u16 {
+            let base = 0x2000 + name_table * 0x400;
+            assert_eq!(ppu.mirror_vram_addr(base), 0x0000);
+            assert_eq!(ppu.mirror_vram_addr(base + 0x3FF), 0x03FF);
+        }
+
+        // Single-screen B maps every nametable to the second 1KB
+        ppu.set_mirroring(Mirroring::SingleScreenB);
+        for name_table in 0..4u16 {
+            let base = 0x2000 + name_table * 0x400;
+            assert_eq!(ppu.mirror_vram_addr(base), 0x0400);
+            assert_eq!(ppu.mirror_vram_addr(base + 0x3FF), 0x07FF);
+        }
+    }
+
+    #[test]
+    fn test_set_mirroring_one_screen() {
+        let mut ppu = create_test_ppu(Mirroring::Horizontal);
+        ppu.set_mirroring(Mirroring::SingleScreenB);
+        assert_eq!(ppu.mirroring, Mirroring::SingleScreenB);
+
+        // Write through nametable 0
+        ppu.write_to_ppu_addr(0x20);
+        ppu.write_to_ppu_addr(0x05);
+        ppu.write_to_data(0x77);
+
+        // All four nametables alias the same 1KB
+        for addr in [0x2005, 0x2405, 0x2805, 0x2C05] {
+            assert_eq!(ppu.mirror_vram_addr(addr), 0x0405);
+
+            ppu.write_to_ppu_addr((addr >> 8) as u8);
+            ppu.write_to_ppu_addr(addr as u8);
+            ppu.read_data(); // Fill the read buffer
+            assert_eq!(ppu.read_data(), 0x77);
+        }
+    }
+
     #[test]
     fn test_vram_addr_increment() {
         let mut ppu = create_test_ppu(Mirroring::Vertical);
@@ -483,6 +1044,27 @@ mod ppu_tests {
         assert_eq!(ppu.oam_addr.get(), 0x12);
     }
 
+    #[test]
+    fn test_write_to_oam_data_ignored_while_rendering() {
+        let mut ppu = create_test_ppu(Mirroring::Vertical);
+        ppu.write_to_mask(0b0001_1000); // Background and sprites on
+        ppu.tick(341 * 100); // Visible scanline 100
+
+        ppu.oam_addr.update(0x10);
+        ppu.write_to_oam_data(0xAB);
+
+        assert!(ppu.oam_data.iter().all(|&x| x == 0));
+        assert_eq!(ppu.oam_addr.get(), 0x14);
+
+        // During VBlank the write goes through; rendering reset OAMADDR
+        ppu.tick(341 * 141);
+        assert_eq!(ppu.oam_addr.get(), 0x00);
+        ppu.oam_addr.update(0x14);
+        ppu.write_to_oam_data(0xAB);
+        assert_eq!(ppu.oam_data[0x14], 0xAB);
+        assert_eq!(ppu.oam_addr.get(), 0x15);
+    }
+
     #[test]
     fn test_read_oam_data() {
         let mut ppu = create_test_ppu(Mirroring::Vertical);
@@ -500,6 +1082,139 @@ mod ppu_tests {
         assert_eq!(ppu.oam_addr.get(), 0x20);
     }
 
+    #[test]
+    fn test_read_oam_data_masks_attribute_bits() {
+        let mut ppu = create_test_ppu(Mirroring::Vertical);
+
+        // Y, tile, attributes and X of sprite 1
+        ppu.oam_addr.update(0x04);
+        for _ in 0..4 {
+            ppu.write_to_oam_data(0xFF);
+        }
+        assert_eq!(ppu.oam_addr.get(), 0x08);
+
+        let read_back: Vec<u8> = (0x04..0x08)
+            .map(|addr| {
+                ppu.oam_addr.update(addr);
+                ppu.read_oam_data()
+            })
+            .collect();
+        assert_eq!(read_back, vec![0xFF, 0xFF, 0xE3, 0xFF]);
+    }
+
+    #[test]
+    fn test_load_oam() {
+        let mut ppu = create_test_ppu(Mirroring::Vertical);
+        let data: [u8; 256] = std::array::from_fn(|i| i as u8);
+
+        ppu.load_oam(&data);
+        assert_eq!(debug::oam(&ppu), &data);
+
+        let sprites = debug::oam_dump(&ppu);
+        assert_eq!(
+            sprites[63],
+            Sprite {
+                y: 0xFC,
+                tile: 0xFD,
+                attributes: 0xFE,
+                x: 0xFF,
+            }
+        );
+        assert_eq!(sprites[63].palette(), 0b10);
+        assert!(sprites[63].behind_background());
+        assert!(sprites[63].flip_horizontal());
+        assert!(sprites[63].flip_vertical());
+        assert_eq!(sprites[0].x, 0x03);
+    }
+
+    // Eight sprites on scanline 10 followed by two off-line sprites
+    fn create_overflow_oam(ninth_tile: u8, tenth_y: u8) -> [u8; 256] {
+        let mut oam = [0xFF; 256];
+        for n in 0..8 {
+            oam[n * 4] = 10;
+        }
+        oam[8 * 4] = 200;
+        oam[9 * 4] = tenth_y;
+        oam[9 * 4 + 1] = ninth_tile;
+        oam
+    }
+
+    #[test]
+    fn test_sprite_overflow_false_negative() {
+        let mut ppu = create_test_ppu(Mirroring::Vertical);
+        // A real ninth sprite on the line at index 9
+        ppu.load_oam(&create_overflow_oam(0x50, 10));
+
+        assert!(ppu.evaluate_sprite_overflow(10));
+
+        // The buggy search checks sprite 9's tile byte instead of its Y
+        ppu.sprite_overflow_mode = SpriteOverflowMode::Hardware;
+        assert!(!ppu.evaluate_sprite_overflow(10));
+    }
+
+    #[test]
+    fn test_sprite_overflow_false_positive() {
+        let mut ppu = create_test_ppu(Mirroring::Vertical);
+        // Only eight sprites on the line, but sprite 9's tile byte looks like a Y of 10
+        ppu.load_oam(&create_overflow_oam(10, 200));
+
+        assert!(!ppu.evaluate_sprite_overflow(10));
+
+        ppu.sprite_overflow_mode = SpriteOverflowMode::Hardware;
+        assert!(ppu.evaluate_sprite_overflow(10));
+    }
+
+    #[test]
+    fn test_sprite_overflow_sets_status_while_rendering() {
+        let mut ppu = create_test_ppu(Mirroring::Vertical);
+        ppu.load_oam(&create_overflow_oam(0x50, 10));
+
+        // Nothing is evaluated with rendering off
+        ppu.tick(341 * 20);
+        assert_eq!(ppu.read_status() & 0b0010_0000, 0);
+
+        ppu.write_to_mask(0b0001_0000);
+        ppu.tick(341 * 262);
+        assert_eq!(ppu.read_status() & 0b0010_0000, 0b0010_0000);
+    }
+
+    #[test]
+    fn test_sprite_evaluation_starts_at_oam_addr() {
+        let mut ppu = create_test_ppu(Mirroring::Vertical);
+        let mut oam = [0xFF; 256];
+        for n in 0..9 {
+            oam[n * 4] = 10;
+        }
+        ppu.load_oam(&oam);
+        assert!(ppu.evaluate_sprite_overflow(10));
+
+        // Starting at sprite 1 leaves only eight sprites on the line
+        ppu.write_to_oam_addr(0x04);
+        assert!(!ppu.evaluate_sprite_overflow(10));
+        ppu.write_to_oam_addr(0x07); // Low bits are ignored
+        assert!(!ppu.evaluate_sprite_overflow(10));
+    }
+
+    #[test]
+    fn test_oam_addr_reset_during_rendering() {
+        let mut ppu = create_test_ppu(Mirroring::Vertical);
+        ppu.write_to_oam_addr(0x20);
+
+        // Without rendering the address is left alone
+        ppu.tick(341 * 10);
+        assert_eq!(ppu.oam_addr.get(), 0x20);
+
+        ppu.write_to_mask(0b0000_1000);
+        ppu.tick(341);
+        assert_eq!(ppu.oam_addr.get(), 0x00);
+
+        // During VBlank it sticks again
+        ppu.tick(341 * (241 - 11));
+        ppu.write_to_oam_addr(0x20);
+        ppu.tick(341 * 10);
+        assert_eq!(ppu.oam_addr.get(), 0x20);
+    }
+
     #[test]
     fn test_oam_address_wrap() {
         let mut ppu = create_test_ppu(Mirroring::Vertical);
@@ -514,25 +1229,131 @@ mod ppu_tests {
         assert_eq!(ppu.oam_data[0xFF], 0x99);
     }
 
+    #[test]
+    fn test_warmup_ignores_ctrl_writes() {
+        let mut ppu = create_test_ppu(Mirroring::Vertical);
+        ppu.begin_warmup();
+
+        ppu.write_to_ctrl(0b1000_0100);
+        assert_eq!(ppu.ctrl_bits(), 0);
+
+        ppu.tick(WARMUP_CPU_CYCLES * 3 - 1);
+        ppu.write_to_ctrl(0b1000_0100);
+        assert_eq!(ppu.ctrl_bits(), 0);
+
+        ppu.tick(1);
+        assert!(!ppu.in_warmup());
+        ppu.write_to_ctrl(0b1000_0100);
+        assert_eq!(ppu.ctrl_bits(), 0b1000_0100);
+    }
+
+    #[test]
+    fn test_warmup_address_writes_toggle_latch() {
+        let mut ppu = create_test_ppu(Mirroring::Vertical);
+        ppu.begin_warmup();
+
+        ppu.write_to_ppu_addr(0x21);
+        assert!(ppu.w_reg);
+        ppu.write_to_ppu_addr(0x08);
+        assert!(!ppu.w_reg);
+        assert_eq!(ppu.vram_addr(), 0x0000);
+        assert_eq!(ppu.v_reg, 0x0000);
+
+        // A dropped $2005 write leaves the next $2006 write as the low byte
+        ppu.write_to_scroll(0x10);
+        assert_eq!(ppu.current_scroll(), (0, 0));
+        ppu.tick(WARMUP_CPU_CYCLES * 3);
+        ppu.write_to_ppu_addr(0x08);
+        assert!(!ppu.w_reg);
+        assert_eq!(ppu.v_reg & 0x00FF, 0x08);
+    }
+
     #[test]
     fn test_write_to_scroll() {
         let mut ppu = create_test_ppu(Mirroring::Vertical);
 
         // Test initial w_reg state
-        assert_eq!(ppu.w_reg, false);
+        assert!(!ppu.w_reg);
 
         // First write should set X scroll
         ppu.write_to_scroll(0x10);
-        assert_eq!(ppu.w_reg, true);
+        assert!(ppu.w_reg);
 
         // Second write should set Y scroll
         ppu.write_to_scroll(0x20);
-        assert_eq!(ppu.w_reg, false);
+        assert!(!ppu.w_reg);
 
         // Check scroll register value
         assert_eq!(ppu.scroll.get(), 0x1020);
     }
 
+    #[test]
+    fn test_scroll_writes_update_t_reg() {
+        let mut ppu = create_test_ppu(Mirroring::Vertical);
+
+        ppu.write_to_ctrl(0b0000_0011); // Nametable 3
+        ppu.write_to_scroll(0x7D); // Coarse X 15, fine X 5
+        ppu.write_to_scroll(0x5E); // Coarse Y 11, fine Y 6
+
+        assert_eq!(ppu.t_reg, (6 << 12) | (3 << 10) | (11 << 5) | 15);
+        assert_eq!(ppu.x_reg, 5);
+    }
+
+    #[test]
+    fn test_current_scroll() {
+        let mut ppu = create_test_ppu(Mirroring::Vertical);
+
+        ppu.write_to_ctrl(0b0000_0001); // Right-hand nametable
+        ppu.write_to_scroll(0x7D);
+        ppu.write_to_scroll(0x5E);
+        ppu.v_reg = ppu.t_reg; // Copy t to v as the PPU does before rendering
+
+        assert_eq!(ppu.current_scroll(), (256 + 0x7D, 0x5E));
+    }
+
+    #[test]
+    fn test_current_scroll_after_ppu_addr_write() {
+        let mut ppu = create_test_ppu(Mirroring::Vertical);
+
+        // The second $2006 write copies t into v immediately
+        ppu.write_to_ppu_addr(0x08); // Nametable 2, fine Y 0
+        ppu.write_to_ppu_addr(0x43); // Coarse Y 2, coarse X 3
+
+        assert_eq!(ppu.v_reg, 0x0843);
+        assert_eq!(ppu.current_scroll(), (3 * 8, 240 + 2 * 8));
+    }
+
+    #[test]
+    fn test_v_reg_only_advances_while_rendering() {
+        let mut ppu = create_test_ppu(Mirroring::Vertical);
+        ppu.write_to_mask(0b0000_1000); // Background on, scanline 0 dot 0
+
+        ppu.tick(16); // Coarse X increments at dots 8 and 16
+        assert_eq!(ppu.v_reg, 2);
+
+        ppu.write_to_mask(0);
+        ppu.tick(100);
+        assert_eq!(ppu.v_reg, 2);
+
+        ppu.write_to_mask(0b0000_1000);
+        ppu.tick(4); // Dot 120
+        assert_eq!(ppu.v_reg, 3);
+    }
+
+    #[test]
+    fn test_v_reg_end_of_scanline_updates() {
+        let mut ppu = create_test_ppu(Mirroring::Vertical);
+        ppu.write_to_scroll(0x28); // t: coarse X 5
+        ppu.write_to_scroll(0);
+        ppu.write_to_mask(0b0000_1000);
+
+        ppu.tick(257);
+        assert_eq!(ppu.v_reg, 0x1000 | 5); // Fine Y incremented, X copied from t
+
+        ppu.tick(341 - 257); // Dots 328 and 336 prefetch the next line's tiles
+        assert_eq!(ppu.v_reg, 0x1000 | 7);
+    }
+
     #[test]
     fn test_read_status() {
         let mut ppu = create_test_ppu(Mirroring::Vertical);
@@ -542,25 +1363,44 @@ mod ppu_tests {
 
         // Reading status should clear w_reg
         let status = ppu.read_status();
-        assert_eq!(ppu.w_reg, false);
+        assert!(!ppu.w_reg);
         assert_eq!(status, ppu.status.bits());
     }
 
+    #[test]
+    fn test_read_status_race_suppresses_vblank() {
+        let mut ppu = create_test_ppu(Mirroring::Vertical);
+        ppu.write_to_ctrl(0b1000_0000); // Enable NMI on VBlank
+
+        // One dot before VBlank is set
+        ppu.tick(241 * 341 - 1);
+        assert_eq!(ppu.read_status() & 0x80, 0);
+
+        assert!(ppu.tick(1));
+        assert_eq!(ppu.read_status() & 0x80, 0);
+        assert!(!ppu.get_nmi_flag());
+
+        // The following frame is unaffected
+        ppu.tick(262 * 341);
+        assert_eq!(ppu.read_status() & 0x80, 0x80);
+        assert!(ppu.get_nmi_flag());
+    }
+
     #[test]
     fn test_status_register_interaction() {
         let mut ppu = create_test_ppu(Mirroring::Vertical);
 
         // Set w_reg by writing to scroll
         ppu.write_to_scroll(0x10);
-        assert_eq!(ppu.w_reg, true);
+        assert!(ppu.w_reg);
 
         // Reading status should reset w_reg
         ppu.read_status();
-        assert_eq!(ppu.w_reg, false);
+        assert!(!ppu.w_reg);
 
         // Next scroll write should affect X again (not Y)
         ppu.write_to_scroll(0x30);
-        assert_eq!(ppu.w_reg, true);
+        assert!(ppu.w_reg);
     }
 
     #[test]
@@ -576,7 +1416,7 @@ mod ppu_tests {
 
         // Test other control bits are preserved
         ppu.write_to_ctrl(0xFF);
-        assert_eq!(ppu.ctrl.bits(), 0xFF);
+        assert_eq!(ppu.ctrl_bits(), 0xFF);
         assert_eq!(ppu.ctrl.vram_addr_increment(), 32);
     }
 
@@ -768,11 +1608,12 @@ mod ppu_tests {
         let mut ppu = create_test_ppu(Mirroring::Vertical);
 
         // Test exact scanline 241 behavior
-        ppu.tick(240 * 341 + 340); // Just before scanline 241
+        // Two dots early; one dot early would hit the suppression race
+        ppu.tick(240 * 341 + 339);
         let status_bits = ppu.read_status();
         assert!(!PPUSTATUS::from_bits_truncate(status_bits).contains(PPUSTATUS::VBLANK));
 
-        ppu.tick(1); // Cross into scanline 241
+        ppu.tick(2); // Cross into scanline 241
         let status_bits = ppu.read_status();
         assert!(PPUSTATUS::from_bits_truncate(status_bits).contains(PPUSTATUS::VBLANK));
 
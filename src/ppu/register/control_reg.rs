@@ -17,6 +17,7 @@ bitflags! {
   // |          (0: read backdrop from EXT pins; 1: output color on EXT pins)
   // +--------- Generate an NMI at the start of the
   //            vertical blanking interval (0: off; 1: on)
+  #[derive(Debug, Clone, Copy)]
   pub struct PPUCTRL: u8 {
     const NAMETABLE1              = 0b00000001;
     const NAMETABLE2              = 0b00000010;
@@ -42,6 +43,36 @@ impl PPUCTRL {
         }
     }
 
+    // Only used by 8x8 sprites
+    pub fn sprite_pattern_addr(&self) -> u16 {
+        if !self.contains(PPUCTRL::SPRITE_PATTERN_ADDR) {
+            0
+        } else {
+            0x1000
+        }
+    }
+
+    pub fn background_pattern_addr(&self) -> u16 {
+        if !self.contains(PPUCTRL::BACKROUND_PATTERN_ADDR) {
+            0
+        } else {
+            0x1000
+        }
+    }
+
+    // 0-3 for $2000, $2400, $2800 and $2C00
+    pub fn base_nametable(&self) -> u8 {
+        self.bits() & 0b11
+    }
+
+    pub fn sprite_size(&self) -> u8 {
+        if !self.contains(PPUCTRL::SPRITE_SIZE) {
+            8
+        } else {
+            16
+        }
+    }
+
     pub fn update(&mut self, data: u8) {
         *self = PPUCTRL::from_bits_truncate(data);
     }
@@ -18,6 +18,7 @@ bitflags! {
   // ||+------- Emphasize red (green on PAL/Dendy)
   // |+-------- Emphasize green (red on PAL/Dendy)
   // +--------- Emphasize blue
+  #[derive(Debug, Clone, Copy)]
   pub struct PPUMASK: u8 {
     const GRAYSCALE         = 0b00000001;
     const LEFT_BACKGROUND   = 0b00000010;
@@ -39,6 +40,7 @@ bitflags! {
   // ||+------- Sprite overflow flag
   // |+-------- Sprite 0 hit flag
   // +--------- Vblank flag, cleared on read. Unreliable; see below.
+  #[derive(Debug, Clone, Copy)]
   pub struct PPUSTATUS: u8 {
     const OPEN_0          = 0b00000001;
     const OPEN_1          = 0b00000010;
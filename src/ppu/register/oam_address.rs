@@ -1,3 +1,4 @@
+#[derive(Clone, Copy)]
 pub struct OAMADDRESS(u8);
 
 impl OAMADDRESS {
@@ -13,6 +14,11 @@ impl OAMADDRESS {
         self.0 = self.0.wrapping_add(1);
     }
 
+    // Bumps only the sprite index (high 6 bits), as glitched writes do
+    pub fn increment_sprite(&mut self) {
+        self.0 = self.0.wrapping_add(4);
+    }
+
     pub fn get(&self) -> u8 {
         self.0
     }
@@ -1,3 +1,4 @@
+#[derive(Clone, Copy)]
 pub struct PPUADDRESS(u8, u8);
 
 impl PPUADDRESS {
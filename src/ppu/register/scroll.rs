@@ -1,3 +1,4 @@
+#[derive(Clone, Copy)]
 pub struct PPUSCROLL(u8, u8);
 
 impl PPUSCROLL {
@@ -0,0 +1,45 @@
+// How the sprite overflow flag is computed during sprite evaluation
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SpriteOverflowMode {
+    // Set whenever more than eight sprites share a scanline
+    Clean,
+    // Replicates the 2C02 evaluation bug that reads OAM diagonally after
+    // the eighth sprite, giving false positives and negatives
+    Hardware,
+}
+
+// One OAM entry, in the order the bytes are stored
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Sprite {
+    pub y: u8,
+    pub tile: u8,
+    pub attributes: u8,
+    pub x: u8,
+}
+
+impl Sprite {
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        Sprite {
+            y: bytes[0],
+            tile: bytes[1],
+            attributes: bytes[2],
+            x: bytes[3],
+        }
+    }
+
+    pub fn palette(&self) -> u8 {
+        self.attributes & 0b11
+    }
+
+    pub fn behind_background(&self) -> bool {
+        self.attributes & 0b0010_0000 != 0
+    }
+
+    pub fn flip_horizontal(&self) -> bool {
+        self.attributes & 0b0100_0000 != 0
+    }
+
+    pub fn flip_vertical(&self) -> bool {
+        self.attributes & 0b1000_0000 != 0
+    }
+}
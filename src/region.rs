@@ -0,0 +1,25 @@
+// TV system the console is built for, which sets the CPU:PPU clock ratio
+// and the number of scanlines per frame
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum Region {
+    #[default]
+    Ntsc,
+    Pal,
+}
+
+impl Region {
+    pub fn scanlines_per_frame(&self) -> u32 {
+        match self {
+            Region::Ntsc => 262,
+            Region::Pal => 312,
+        }
+    }
+
+    // PPU dots per CPU cycle in fifths: 3 for NTSC, 3.2 for PAL
+    pub fn dot_fifths_per_cpu_cycle(&self) -> u32 {
+        match self {
+            Region::Ntsc => 15,
+            Region::Pal => 16,
+        }
+    }
+}
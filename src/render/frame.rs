@@ -0,0 +1,35 @@
+pub struct Frame {
+    pub data: Vec<u8>,
+}
+
+impl Frame {
+    pub const WIDTH: usize = 256;
+    pub const HIGHT: usize = 240;
+
+    pub fn new() -> Self {
+        Frame {
+            data: vec![0; (Frame::WIDTH) * (Frame::HIGHT) * 3],
+        }
+    }
+
+    pub fn fill(&mut self, rgb: (u8, u8, u8)) {
+        for pixel in self.data.chunks_exact_mut(3) {
+            pixel.copy_from_slice(&[rgb.0, rgb.1, rgb.2]);
+        }
+    }
+
+    pub fn set_pixel(&mut self, x: usize, y: usize, rgb: (u8, u8, u8)) {
+        let base = y * 3 * Frame::WIDTH + x * 3;
+        if base + 2 < self.data.len() {
+            self.data[base] = rgb.0;
+            self.data[base + 1] = rgb.1;
+            self.data[base + 2] = rgb.2;
+        }
+    }
+}
+
+impl Default for Frame {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,393 @@
+use crate::{
+    ppu::{PPU, debug},
+    render::{frame::Frame, palette::palette_to_rgb},
+};
+
+pub mod frame;
+pub mod palette;
+
+// Receives every pixel of a frame as a NES palette index (0x00-0x3F) plus
+// the $2001 emphasis bits in effect, so color conversion and scaling are up
+// to the implementation
+pub trait PixelSink {
+    fn put_pixel(&mut self, x: usize, y: usize, color_index: u8, emphasis: u8);
+}
+
+// The default sink, converting through the system palette to RGB24
+pub type RgbFrame = Frame;
+
+impl PixelSink for Frame {
+    fn put_pixel(&mut self, x: usize, y: usize, color_index: u8, emphasis: u8) {
+        self.set_pixel(x, y, palette_to_rgb(color_index, emphasis));
+    }
+}
+
+pub fn render(ppu: &PPU, frame: &mut Frame) {
+    // The universal background color at $3F00 shows wherever nothing opaque is drawn
+    frame.fill(palette_to_rgb(ppu.palette_table[0], ppu.emphasis()));
+    render_to(ppu, frame);
+}
+
+// Drives `sink` with each of the 256x240 pixels exactly once
+pub fn render_to<S: PixelSink + ?Sized>(ppu: &PPU, sink: &mut S) {
+    let mut pixels = vec![0; Frame::WIDTH * Frame::HIGHT];
+    let mut background_opaque = vec![false; Frame::WIDTH * Frame::HIGHT];
+    render_background(ppu, &mut pixels, &mut background_opaque);
+    if ppu.sprites_enabled() {
+        render_sprites(ppu, &mut pixels, &background_opaque);
+    }
+
+    let emphasis = ppu.emphasis();
+    for (i, &color_index) in pixels.iter().enumerate() {
+        sink.put_pixel(i % Frame::WIDTH, i / Frame::WIDTH, color_index, emphasis);
+    }
+}
+
+fn render_background(ppu: &PPU, pixels: &mut [u8], opaque: &mut [bool]) {
+    for i in 0..0x3C0 {
+        let tile_index = ppu.vram[i] as usize;
+        let tile_column = i % 32;
+        let tile_row = i / 32;
+        let palette = background_palette(ppu, tile_column, tile_row);
+
+        for y in 0..8 {
+            let bank = ppu.background_pattern_addr(tile_row * 8 + y) as usize;
+            let tile_addr = bank + tile_index * 16;
+            let mut plane_lo = chr_byte(ppu, tile_addr + y);
+            let mut plane_hi = chr_byte(ppu, tile_addr + y + 8);
+
+            for x in (0..8).rev() {
+                let value = (plane_lo & 1) | (plane_hi & 1) << 1;
+                plane_lo >>= 1;
+                plane_hi >>= 1;
+                // Color 0 is transparent and always the backdrop
+                let pixel = (tile_row * 8 + y) * Frame::WIDTH + tile_column * 8 + x;
+                pixels[pixel] = palette[value as usize];
+                opaque[pixel] = value != 0;
+            }
+        }
+    }
+}
+
+fn render_sprites(ppu: &PPU, pixels: &mut [u8], background_opaque: &[bool]) {
+    let height = ppu.sprite_size() as usize;
+    // Lower OAM indices have priority, so they are drawn last
+    for sprite in debug::oam_dump(ppu).iter().rev() {
+        // Sprites appear one scanline below their Y coordinate
+        let top = sprite.y as usize + 1;
+        let palette_start = 0x10 + sprite.palette() as usize * 4;
+
+        for row in 0..height {
+            let y = top + row;
+            if y >= Frame::HIGHT {
+                break;
+            }
+            let tile_row = if sprite.flip_vertical() {
+                height - 1 - row
+            } else {
+                row
+            };
+            let tile_addr = sprite_tile_addr(ppu, sprite.tile, tile_row, height, y);
+            let plane_lo = chr_byte(ppu, tile_addr);
+            let plane_hi = chr_byte(ppu, tile_addr + 8);
+
+            for column in 0..8 {
+                let x = sprite.x as usize + column;
+                if x >= Frame::WIDTH {
+                    break;
+                }
+                let bit = if sprite.flip_horizontal() {
+                    column
+                } else {
+                    7 - column
+                };
+                let value = (plane_lo >> bit & 1) | (plane_hi >> bit & 1) << 1;
+                let pixel = y * Frame::WIDTH + x;
+                if value == 0 || (sprite.behind_background() && background_opaque[pixel]) {
+                    continue;
+                }
+                pixels[pixel] = ppu.palette_table[palette_start + value as usize];
+            }
+        }
+    }
+}
+
+// Address of pattern row `row` of a sprite drawn on `scanline`. 8x8 sprites
+// use the table PPUCTRL bit 3 selected for that line; 8x16 sprites pick it
+// with bit 0 of the tile number and use an even/odd tile pair
+fn sprite_tile_addr(ppu: &PPU, tile: u8, row: usize, height: usize, scanline: usize) -> usize {
+    if height == 16 {
+        let bank = (tile & 1) as usize * 0x1000;
+        let tile = (tile & 0xFE) as usize + row / 8;
+        bank + tile * 16 + row % 8
+    } else {
+        ppu.sprite_pattern_addr(scanline) as usize + tile as usize * 16 + row
+    }
+}
+
+// Carts without CHR ROM render as blank tiles instead of panicking
+fn chr_byte(ppu: &PPU, addr: usize) -> u8 {
+    ppu.chr_rom.get(addr).copied().unwrap_or(0)
+}
+
+fn background_palette(ppu: &PPU, tile_column: usize, tile_row: usize) -> [u8; 4] {
+    let attr_table_idx = tile_row / 4 * 8 + tile_column / 4;
+    let attr_byte = ppu.vram[0x3C0 + attr_table_idx];
+
+    let palette_idx = match (tile_column % 4 / 2, tile_row % 4 / 2) {
+        (0, 0) => attr_byte & 0b11,
+        (1, 0) => (attr_byte >> 2) & 0b11,
+        (0, 1) => (attr_byte >> 4) & 0b11,
+        (1, 1) => (attr_byte >> 6) & 0b11,
+        _ => unreachable!(),
+    };
+
+    let palette_start = 1 + palette_idx as usize * 4;
+    [
+        ppu.palette_table[0],
+        ppu.palette_table[palette_start],
+        ppu.palette_table[palette_start + 1],
+        ppu.palette_table[palette_start + 2],
+    ]
+}
+
+#[cfg(test)]
+mod render_tests {
+    use super::*;
+    use crate::{mem::rom::Mirroring, render::palette::SYSTEM_PALLETE};
+
+    #[test]
+    fn test_render_background_tile() {
+        let mut chr_rom = vec![0; 0x2000];
+        // Tile 1: every pixel uses color 3 (both planes set)
+        for byte in &mut chr_rom[16..32] {
+            *byte = 0xFF;
+        }
+        let mut ppu = PPU::new(chr_rom, Mirroring::Horizontal);
+        ppu.vram[0] = 1; // Top-left tile
+        ppu.palette_table[0] = 0x0F;
+        ppu.palette_table[3] = 0x30;
+
+        let mut frame = Frame::new();
+        render(&ppu, &mut frame);
+
+        // Inside the first tile
+        assert_eq!(&frame.data[0..3], &[0xFF, 0xFF, 0xFF]);
+        let (r, g, b) = SYSTEM_PALLETE[0x0F];
+        // Pixel (8, 0) belongs to tile 0, which uses the backdrop color
+        assert_eq!(&frame.data[8 * 3..8 * 3 + 3], &[r, g, b]);
+    }
+
+    #[test]
+    fn test_render_backdrop_fills_transparent_pixels() {
+        let mut chr_rom = vec![0; 0x2000];
+        // Tile 1: left half opaque with color 1, right half transparent
+        for byte in &mut chr_rom[16..24] {
+            *byte = 0xF0;
+        }
+        let mut ppu = PPU::new(chr_rom, Mirroring::Horizontal);
+        ppu.vram[0] = 1;
+        ppu.palette_table[1] = 0x30;
+        // Write the backdrop through $3F00
+        ppu.write_to_ppu_addr(0x3F);
+        ppu.write_to_ppu_addr(0x00);
+        ppu.write_to_data(0x16);
+
+        // A larger buffer than the nametable area is filled as well
+        let mut frame = Frame {
+            data: vec![0; Frame::WIDTH * (Frame::HIGHT + 1) * 3],
+        };
+        render(&ppu, &mut frame);
+
+        let (r, g, b) = SYSTEM_PALLETE[0x16];
+        let pixel = |x: usize, y: usize| {
+            let base = (y * Frame::WIDTH + x) * 3;
+            [frame.data[base], frame.data[base + 1], frame.data[base + 2]]
+        };
+        assert_eq!(pixel(0, 0), [0xFF, 0xFF, 0xFF]);
+        assert_eq!(pixel(4, 0), [r, g, b]); // Transparent half of tile 1
+        assert_eq!(pixel(100, 100), [r, g, b]); // Tile 0 is fully transparent
+        assert_eq!(pixel(0, Frame::HIGHT), [r, g, b]); // Outside the nametable
+    }
+
+    #[test]
+    fn test_render_to_custom_sink() {
+        struct CountingSink {
+            calls: usize,
+            covered: Vec<bool>,
+        }
+
+        impl PixelSink for CountingSink {
+            fn put_pixel(&mut self, x: usize, y: usize, _color_index: u8, _emphasis: u8) {
+                self.calls += 1;
+                self.covered[y * Frame::WIDTH + x] = true;
+            }
+        }
+
+        let ppu = PPU::new(vec![0; 0x2000], Mirroring::Horizontal);
+        let mut sink = CountingSink {
+            calls: 0,
+            covered: vec![false; Frame::WIDTH * Frame::HIGHT],
+        };
+        render_to(&ppu, &mut sink);
+
+        assert_eq!(sink.calls, 256 * 240);
+        assert!(sink.covered.iter().all(|&pixel| pixel));
+    }
+
+    #[test]
+    fn test_render_emphasis_attenuates_whole_frame() {
+        let mut ppu = PPU::new(vec![0; 0x2000], Mirroring::Horizontal);
+        ppu.palette_table[0] = 0x30; // White backdrop
+
+        let mut plain = Frame::new();
+        render(&ppu, &mut plain);
+
+        ppu.write_to_mask(0b0010_0000); // Emphasize red
+        let mut emphasized = Frame::new();
+        render(&ppu, &mut emphasized);
+
+        for pixel in [0, 128 * 3, (239 * Frame::WIDTH + 255) * 3] {
+            let before = &plain.data[pixel..pixel + 3];
+            let after = &emphasized.data[pixel..pixel + 3];
+            assert_eq!(after[0], before[0]);
+            assert!(after[1] < before[1]);
+            assert!(after[2] < before[2]);
+        }
+    }
+
+    #[test]
+    fn test_render_to_passes_emphasis() {
+        struct EmphasisSink(Vec<u8>);
+
+        impl PixelSink for EmphasisSink {
+            fn put_pixel(&mut self, _x: usize, _y: usize, _color_index: u8, emphasis: u8) {
+                self.0.push(emphasis);
+            }
+        }
+
+        let mut ppu = PPU::new(vec![0; 0x2000], Mirroring::Horizontal);
+        ppu.palette_table[0] = 0x30; // White backdrop
+        ppu.write_to_mask(0b1010_0000); // Emphasize red and blue
+
+        let mut sink = EmphasisSink(Vec::new());
+        render_to(&ppu, &mut sink);
+        assert!(sink.0.iter().all(|&emphasis| emphasis == 0b101));
+
+        // A plain Frame driven through render_to is tinted too
+        let mut frame = Frame::new();
+        render_to(&ppu, &mut frame);
+        assert_eq!(
+            (frame.data[0], frame.data[1], frame.data[2]),
+            palette_to_rgb(0x30, 0b101)
+        );
+    }
+
+    #[test]
+    fn test_palette_to_rgb_emphasis() {
+        assert_eq!(palette_to_rgb(0x30, 0), SYSTEM_PALLETE[0x30]);
+        // All three bits dim every channel
+        let (r, g, b) = palette_to_rgb(0x30, 0b111);
+        assert!(r < 0xFF && g < 0xFF && b < 0xFF);
+        // Green emphasis keeps green and dims the others
+        let (r, g, b) = palette_to_rgb(0x30, 0b010);
+        assert_eq!(g, SYSTEM_PALLETE[0x30].1);
+        assert!(r < SYSTEM_PALLETE[0x30].0 && b < SYSTEM_PALLETE[0x30].2);
+    }
+
+    #[test]
+    fn test_render_pattern_table_switch_mid_frame() {
+        let mut chr_rom = vec![0; 0x2000];
+        // Tile 1 is solid color 3 in the left pattern table and blank in the right one
+        for byte in &mut chr_rom[16..32] {
+            *byte = 0xFF;
+        }
+        let mut ppu = PPU::new(chr_rom, Mirroring::Horizontal);
+        ppu.vram[..0x3C0].fill(1);
+        ppu.palette_table[0] = 0x0F;
+        ppu.palette_table[3] = 0x30;
+
+        ppu.tick(120 * 341);
+        ppu.write_to_ctrl(0b0001_0000); // Background from $1000 for the rest of the frame
+
+        let mut frame = Frame::new();
+        render(&ppu, &mut frame);
+        let pixel = |y: usize| &frame.data[y * Frame::WIDTH * 3..y * Frame::WIDTH * 3 + 3];
+        let (r, g, b) = SYSTEM_PALLETE[0x0F];
+        assert_eq!(pixel(0), &[0xFF, 0xFF, 0xFF]);
+        assert_eq!(pixel(119), &[0xFF, 0xFF, 0xFF]);
+        assert_eq!(pixel(120), &[r, g, b]);
+        assert_eq!(pixel(239), &[r, g, b]);
+
+        // The next frame fetches every line from $1000
+        ppu.tick(262 * 341);
+        render(&ppu, &mut frame);
+        let pixel = |y: usize| &frame.data[y * Frame::WIDTH * 3..y * Frame::WIDTH * 3 + 3];
+        assert_eq!(pixel(0), &[r, g, b]);
+    }
+
+    #[test]
+    fn test_render_sprites_use_ctrl_pattern_table() {
+        let mut chr_rom = vec![0; 0x2000];
+        chr_rom[0x0010..0x0018].fill(0xFF); // Tile 1 at $0000: color 1
+        chr_rom[0x1018..0x1020].fill(0xFF); // Tile 1 at $1000: color 2
+        let mut ppu = PPU::new(chr_rom, Mirroring::Horizontal);
+        ppu.palette_table[0] = 0x0F;
+        ppu.palette_table[0x11] = 0x16;
+        ppu.palette_table[0x12] = 0x2A;
+
+        let mut oam = [0xFF; 256]; // Everything below the screen
+        oam[..4].copy_from_slice(&[9, 1, 0, 20]); // Sprite 0: tile 1 at (20, 10)
+        ppu.load_oam(&oam);
+        ppu.write_to_mask(0b0001_0000); // Sprites on
+        ppu.write_to_ctrl(0b0000_1000); // 8x8 sprites from $1000
+
+        let mut frame = Frame::new();
+        render(&ppu, &mut frame);
+        let pixel = |frame: &Frame, x: usize, y: usize| {
+            let base = (y * Frame::WIDTH + x) * 3;
+            [frame.data[base], frame.data[base + 1], frame.data[base + 2]]
+        };
+        let (r, g, b) = SYSTEM_PALLETE[0x2A];
+        assert_eq!(pixel(&frame, 20, 10), [r, g, b]);
+        assert_eq!(pixel(&frame, 27, 17), [r, g, b]);
+        let (r, g, b) = SYSTEM_PALLETE[0x0F];
+        assert_eq!(pixel(&frame, 28, 10), [r, g, b]);
+        assert_eq!(pixel(&frame, 20, 18), [r, g, b]);
+
+        ppu.write_to_ctrl(0);
+        render(&ppu, &mut frame);
+        let (r, g, b) = SYSTEM_PALLETE[0x16];
+        assert_eq!(pixel(&frame, 20, 10), [r, g, b]);
+    }
+
+    #[test]
+    fn test_render_sprite_pattern_table_switch_mid_frame() {
+        let mut chr_rom = vec![0; 0x2000];
+        chr_rom[0x0010..0x0018].fill(0xFF); // Tile 1 at $0000: color 1
+        chr_rom[0x1018..0x1020].fill(0xFF); // Tile 1 at $1000: color 2
+        let mut ppu = PPU::new(chr_rom, Mirroring::Horizontal);
+        ppu.palette_table[0x11] = 0x16;
+        ppu.palette_table[0x12] = 0x2A;
+
+        let mut oam = [0xFF; 256];
+        oam[..4].copy_from_slice(&[9, 1, 0, 20]); // Sprite 0: tile 1 at (20, 10)
+        oam[4..8].copy_from_slice(&[199, 1, 0, 20]); // Sprite 1: tile 1 at (20, 200)
+        ppu.load_oam(&oam);
+        ppu.write_to_mask(0b0001_0000);
+
+        ppu.tick(120 * 341);
+        ppu.write_to_ctrl(0b0000_1000); // Sprites from $1000 for the rest of the frame
+
+        let mut frame = Frame::new();
+        render(&ppu, &mut frame);
+        let pixel = |x: usize, y: usize| {
+            let base = (y * Frame::WIDTH + x) * 3;
+            [frame.data[base], frame.data[base + 1], frame.data[base + 2]]
+        };
+        let (r, g, b) = SYSTEM_PALLETE[0x16];
+        assert_eq!(pixel(20, 10), [r, g, b]);
+        let (r, g, b) = SYSTEM_PALLETE[0x2A];
+        assert_eq!(pixel(20, 200), [r, g, b]);
+    }
+}
@@ -5,3 +5,10 @@ pub fn set_bit(byte: u8, mask: u8, value: bool) -> u8 {
         byte & (mask ^ 0b1111_1111)
     }
 }
+
+// 64-bit FNV-1a, used where a hash must stay stable across runs and builds
+pub fn fnv1a<I: IntoIterator<Item = u8>>(bytes: I) -> u64 {
+    bytes.into_iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}